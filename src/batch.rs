@@ -0,0 +1,883 @@
+//! query a list of words with the same settings for every site.
+//!
+//! each word is retried with [`RetryPolicy`],and with a checkpoint file set,every
+//! entry is appended to it as soon as it is done (one json object per line),so that
+//! a killed batch can be run again and only query the words still missing.
+//!
+//! words failing after all retries are collected in a [`FailureReport`],which can be
+//! saved as json and used to retry only those words later.
+//!
+//! a batch stops starting new words once its deadline passes or its
+//! [`CancellationToken`] is cancelled,words not done by then are reported as
+//! [`Error::Cancelled`] and everything finished so far is returned.
+//!
+//! results of a batch keep order of the word list,every one carries its index in
+//! the list and a failed word is kept as an item without entry.
+use crate::{
+    error::{Error, Result},
+    progress::{ProgressEvent, ProgressHandler},
+    retry::RetryPolicy,
+    utils::{batch_execute, DEFAULT_CONCURRENCY},
+};
+use futures::{future, stream, Future, Stream, StreamExt};
+use serde::{de::DeserializeOwned, Deserialize, Serialize};
+use std::{
+    collections::HashMap,
+    fs,
+    io::{BufReader, Write},
+    path::{Path, PathBuf},
+    sync::{
+        atomic::{AtomicUsize, Ordering},
+        Arc, Mutex,
+    },
+    time::Duration,
+};
+use tokio_util::sync::CancellationToken;
+
+/// settings of a batch query
+#[derive(Debug, Clone)]
+pub struct BatchOptions {
+    /// max number of requests in flight
+    concurrency: usize,
+    retry: RetryPolicy,
+    /// json lines file recording finished entries,`None` means not resumable
+    checkpoint: Option<PathBuf>,
+    /// where [`FailureReport`] is saved after batch ends,`None` means not saved
+    report: Option<PathBuf>,
+    progress: Option<ProgressHandler>,
+    /// time allowed to the whole batch,`None` means no limit
+    deadline: Option<Duration>,
+    cancellation: Option<CancellationToken>,
+}
+
+impl Default for BatchOptions {
+    fn default() -> Self {
+        Self {
+            concurrency: DEFAULT_CONCURRENCY,
+            retry: RetryPolicy::default(),
+            checkpoint: None,
+            report: None,
+            progress: None,
+            deadline: None,
+            cancellation: None,
+        }
+    }
+}
+
+impl BatchOptions {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn set_concurrency(&mut self, concurrency: usize) {
+        self.concurrency = concurrency;
+    }
+
+    pub fn set_retry(&mut self, retry: RetryPolicy) {
+        self.retry = retry;
+    }
+
+    pub fn set_checkpoint<P: Into<PathBuf>>(&mut self, checkpoint: Option<P>) {
+        self.checkpoint = checkpoint.map(Into::into);
+    }
+
+    pub fn set_report<P: Into<PathBuf>>(&mut self, report: Option<P>) {
+        self.report = report.map(Into::into);
+    }
+
+    pub fn set_progress(&mut self, progress: Option<ProgressHandler>) {
+        self.progress = progress;
+    }
+
+    pub fn set_deadline(&mut self, deadline: Option<Duration>) {
+        self.deadline = deadline;
+    }
+
+    pub fn set_cancellation(&mut self, cancellation: Option<CancellationToken>) {
+        self.cancellation = cancellation;
+    }
+
+    pub fn concurrency(&self) -> usize {
+        self.concurrency
+    }
+
+    pub fn retry(&self) -> &RetryPolicy {
+        &self.retry
+    }
+
+    pub fn checkpoint(&self) -> Option<&Path> {
+        self.checkpoint.as_deref()
+    }
+
+    pub fn report(&self) -> Option<&Path> {
+        self.report.as_deref()
+    }
+
+    pub fn progress(&self) -> Option<&ProgressHandler> {
+        self.progress.as_ref()
+    }
+
+    pub fn deadline(&self) -> Option<Duration> {
+        self.deadline
+    }
+
+    pub fn cancellation(&self) -> Option<&CancellationToken> {
+        self.cancellation.as_ref()
+    }
+
+    /// complete once deadline passes or token is cancelled,deadline counts from the
+    /// first poll.
+    async fn stopped(&self) {
+        let cancelled = async {
+            match self.cancellation.as_ref() {
+                Some(token) => token.cancelled().await,
+                None => future::pending().await,
+            }
+        };
+        let expired = async {
+            match self.deadline {
+                Some(deadline) => tokio::time::sleep(deadline).await,
+                None => future::pending().await,
+            }
+        };
+        tokio::select! {
+            _ = cancelled => {}
+            _ = expired => {}
+        }
+    }
+
+    fn emit(&self, event: ProgressEvent) {
+        if let Some(progress) = self.progress.as_ref() {
+            progress.emit(event);
+        }
+    }
+}
+
+/// a word which could not be queried
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Failure {
+    /// index of the word in word list of the batch
+    index: usize,
+    word: String,
+    url: String,
+    /// see [`Error::kind`]
+    kind: String,
+    /// site the word was queried from,empty if the error does not tell
+    #[serde(default)]
+    source: String,
+    /// see [`Error::status`]
+    #[serde(default)]
+    status: Option<u16>,
+    /// element missing from the page,see [`Error::MissingField`]
+    #[serde(default)]
+    field: Option<String>,
+    attempts: u32,
+    /// message of the last error
+    message: String,
+}
+
+impl Failure {
+    fn new(index: usize, word: String, url: String, error: &Error, attempts: u32) -> Self {
+        Self {
+            index,
+            word,
+            url,
+            kind: error.kind().into(),
+            source: error
+                .context()
+                .map(|c| c.source().to_string())
+                .unwrap_or_default(),
+            status: error.status(),
+            field: match error {
+                Error::MissingField { field, .. } => Some(field.clone()),
+                _ => None,
+            },
+            attempts,
+            message: error.to_string(),
+        }
+    }
+
+    pub fn index(&self) -> usize {
+        self.index
+    }
+
+    pub fn word(&self) -> &str {
+        self.word.as_ref()
+    }
+
+    pub fn url(&self) -> &str {
+        self.url.as_ref()
+    }
+
+    pub fn kind(&self) -> &str {
+        self.kind.as_ref()
+    }
+
+    pub fn source(&self) -> &str {
+        self.source.as_ref()
+    }
+
+    pub fn status(&self) -> Option<u16> {
+        self.status
+    }
+
+    pub fn field(&self) -> Option<&str> {
+        self.field.as_deref()
+    }
+
+    pub fn attempts(&self) -> u32 {
+        self.attempts
+    }
+
+    pub fn message(&self) -> &str {
+        self.message.as_ref()
+    }
+}
+
+/// all failures of one batch
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct FailureReport {
+    failures: Vec<Failure>,
+}
+
+impl FailureReport {
+    pub fn load<P: AsRef<Path>>(path: P) -> Result<Self> {
+        Ok(serde_json::from_reader(BufReader::new(fs::File::open(
+            path,
+        )?))?)
+    }
+
+    pub fn save<P: AsRef<Path>>(&self, path: P) -> Result<()> {
+        fs::write(path, serde_json::to_string_pretty(self)?)?;
+        Ok(())
+    }
+
+    pub fn failures(&self) -> &[Failure] {
+        self.failures.as_ref()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.failures.is_empty()
+    }
+
+    /// failed words,e.g. to query them again
+    pub fn words(&self) -> Vec<String> {
+        self.failures.iter().map(|f| f.word.clone()).collect()
+    }
+}
+
+/// result of one word of a batch
+#[derive(Debug, Clone, PartialEq)]
+pub struct BatchItem<E> {
+    /// index of the word in word list of the batch
+    index: usize,
+    word: String,
+    /// `None` if the word failed,see [`FailureReport`] for why
+    entry: Option<E>,
+}
+
+impl<E> BatchItem<E> {
+    pub fn index(&self) -> usize {
+        self.index
+    }
+
+    pub fn word(&self) -> &str {
+        self.word.as_ref()
+    }
+
+    pub fn entry(&self) -> Option<&E> {
+        self.entry.as_ref()
+    }
+
+    pub fn is_failed(&self) -> bool {
+        self.entry.is_none()
+    }
+
+    pub fn into_entry(self) -> Option<E> {
+        self.entry
+    }
+}
+
+/// results and failures of one batch,both in order of word list
+#[derive(Debug, Clone, Default)]
+pub struct BatchOutput<E> {
+    items: Vec<BatchItem<E>>,
+    report: FailureReport,
+    /// why report file could not be written,entries are kept anyway
+    report_error: Option<Arc<Error>>,
+}
+
+impl<E> BatchOutput<E> {
+    /// one item for every word,failed ones included
+    pub fn items(&self) -> &[BatchItem<E>] {
+        self.items.as_ref()
+    }
+
+    /// entries of words which succeeded
+    pub fn entries(&self) -> impl Iterator<Item = &E> {
+        self.items.iter().filter_map(BatchItem::entry)
+    }
+
+    pub fn report(&self) -> &FailureReport {
+        &self.report
+    }
+
+    /// error of saving report to file of [`BatchOptions::set_report`],if any
+    pub fn report_error(&self) -> Option<&Error> {
+        self.report_error.as_deref()
+    }
+
+    pub fn into_items(self) -> Vec<BatchItem<E>> {
+        self.items
+    }
+
+    pub fn into_entries(self) -> Vec<E> {
+        self.items
+            .into_iter()
+            .filter_map(BatchItem::into_entry)
+            .collect()
+    }
+
+    pub fn into_parts(self) -> (Vec<BatchItem<E>>, FailureReport) {
+        (self.items, self.report)
+    }
+}
+
+/// one line of checkpoint file
+#[derive(Debug, Serialize, Deserialize)]
+struct Record<W, E> {
+    word: W,
+    entry: E,
+}
+
+/// append-only json lines file of finished entries
+#[derive(Debug)]
+struct Checkpoint {
+    file: Mutex<fs::File>,
+}
+
+impl Checkpoint {
+    /// open checkpoint file and read entries finished by earlier runs.
+    ///
+    /// a line which cannot be parsed,e.g. cut off when process was killed,is skipped.
+    fn open<E: DeserializeOwned>(path: &Path) -> Result<(Self, Vec<Record<String, E>>)> {
+        let mut done = vec![];
+        let mut cut_off = false;
+        if path.exists() {
+            // a line cut off inside a character is not valid utf-8
+            let content = fs::read(path)?;
+            let content = String::from_utf8_lossy(&content);
+            for line in content.lines() {
+                if let Ok(record) = serde_json::from_str(line) {
+                    done.push(record);
+                }
+            }
+            cut_off = !content.is_empty() && !content.ends_with('\n');
+        }
+        let file = fs::OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(path)?;
+        // start on a new line if the last one was cut off
+        if cut_off {
+            writeln!(&file)?;
+        }
+        Ok((
+            Self {
+                file: Mutex::new(file),
+            },
+            done,
+        ))
+    }
+
+    fn append<E: Serialize>(&self, word: &str, entry: &E) -> Result<()> {
+        let line = serde_json::to_string(&Record { word, entry })?;
+        writeln!(self.file.lock().unwrap(), "{}", line)?;
+        Ok(())
+    }
+}
+
+/// state of a batch before any word is queried
+struct Started<E> {
+    checkpoint: Option<Checkpoint>,
+    /// items finished by earlier runs
+    finished: Vec<BatchItem<E>>,
+    /// indexed words still to query
+    pending: Vec<(usize, String)>,
+}
+
+/// open checkpoint file of `options` if any,and find words finished by earlier runs.
+fn start<E: DeserializeOwned + Clone>(
+    options: &BatchOptions,
+    words: &[String],
+) -> Result<Started<E>> {
+    let (checkpoint, done) = match options.checkpoint() {
+        Some(path) => {
+            let (checkpoint, done) = Checkpoint::open::<E>(path)?;
+            (Some(checkpoint), done)
+        }
+        None => (None, vec![]),
+    };
+    let done = done
+        .into_iter()
+        .map(|r| (r.word, r.entry))
+        .collect::<HashMap<_, _>>();
+    let (mut finished, mut pending) = (vec![], vec![]);
+    for (index, word) in words.iter().enumerate() {
+        match done.get(word) {
+            Some(entry) => finished.push(BatchItem {
+                index,
+                word: word.clone(),
+                entry: Some(entry.clone()),
+            }),
+            None => pending.push((index, word.clone())),
+        }
+    }
+    options.emit(ProgressEvent::BatchStarted {
+        total: pending.len(),
+        resumed: finished.len(),
+    });
+    Ok(Started {
+        checkpoint,
+        finished,
+        pending,
+    })
+}
+
+/// query one word with retries and record it in checkpoint file.
+///
+/// return the word,its result and how many attempts were made,failing to write
+/// checkpoint file fails the word,so that it is queried again on resume.
+async fn query_word<E, F, Fut>(
+    options: &BatchOptions,
+    checkpoint: Option<&Checkpoint>,
+    word: String,
+    query: &F,
+) -> (String, Result<E>, u32)
+where
+    E: Serialize,
+    F: Fn(String) -> Fut,
+    Fut: Future<Output = Result<E>>,
+{
+    options.emit(ProgressEvent::Started { word: word.clone() });
+    let on_retry = |attempt, e: &Error, delay| {
+        options.emit(ProgressEvent::Retried {
+            word: word.clone(),
+            attempt,
+            delay,
+            error: e.to_string(),
+        })
+    };
+    let (ret, attempts) = options.retry.run(|| query(word.clone()), on_retry).await;
+    let ret = ret.and_then(|entry| match checkpoint {
+        Some(checkpoint) => checkpoint.append(&word, &entry).map(|_| entry),
+        None => Ok(entry),
+    });
+    match &ret {
+        Ok(_) => options.emit(ProgressEvent::Succeeded { word: word.clone() }),
+        Err(e) => options.emit(ProgressEvent::Failed {
+            word: word.clone(),
+            error: e.to_string(),
+        }),
+    }
+    (word, ret, attempts)
+}
+
+/// query every word with `query`.
+///
+/// `url` gives url of a word for failure report.
+///
+/// failing to save report file doesn't fail the batch,see [`BatchOutput::report_error`].
+pub(crate) async fn run<E, F, Fut, U>(
+    options: &BatchOptions,
+    words: &[String],
+    url: U,
+    query: F,
+) -> Result<BatchOutput<E>>
+where
+    E: Serialize + DeserializeOwned + Clone,
+    F: Fn(String) -> Fut,
+    Fut: Future<Output = Result<E>>,
+    U: Fn(&str) -> Result<String>,
+{
+    let Started {
+        checkpoint,
+        finished: mut items,
+        pending,
+    } = start(options, words)?;
+    let (checkpoint, query) = (checkpoint.as_ref(), &query);
+    let ret = batch_execute(
+        pending.clone(),
+        options.concurrency,
+        options.stopped(),
+        |(index, word)| async move { (index, query_word(options, checkpoint, word, query).await) },
+    )
+    .await;
+
+    let mut report = FailureReport::default();
+    let mut results = ret.into_iter().collect::<HashMap<_, _>>();
+    for (index, word) in pending {
+        let (word, ret, attempts) = results
+            .remove(&index)
+            .unwrap_or_else(|| (word.clone(), Err(Error::Cancelled(word)), 0));
+        let entry = match ret {
+            Ok(entry) => Some(entry),
+            Err(e) => {
+                let url = url(&word).unwrap_or_default();
+                report
+                    .failures
+                    .push(Failure::new(index, word.clone(), url, &e, attempts));
+                None
+            }
+        };
+        items.push(BatchItem { index, word, entry });
+    }
+    // tasks finish in any order
+    items.sort_by_key(|item| item.index);
+    report.failures.sort_by_key(|f| f.index);
+
+    // like a failed checkpoint write,a failed report write must not lose the entries
+    let report_error = options
+        .report()
+        .and_then(|path| report.save(path).err())
+        .map(Arc::new);
+    options.emit(ProgressEvent::BatchFinished {
+        succeeded: items.len() - report.failures.len(),
+        failed: report.failures.len(),
+    });
+    Ok(BatchOutput {
+        items,
+        report,
+        report_error,
+    })
+}
+
+/// same as [`run`],but every entry is yielded as soon as it is done.
+///
+/// entries finished by earlier runs in checkpoint file come first,words failing
+/// after all retries are yielded as errors instead of being collected in a report.
+pub(crate) fn stream<'a, E, F, Fut>(
+    options: &'a BatchOptions,
+    words: &[String],
+    query: F,
+) -> impl Stream<Item = Result<E>> + 'a
+where
+    E: Serialize + DeserializeOwned + Clone + 'a,
+    F: Fn(String) -> Fut + 'a,
+    Fut: Future<Output = Result<E>> + 'a,
+{
+    let Started {
+        checkpoint,
+        finished,
+        pending,
+    } = match start(options, words) {
+        Ok(started) => started,
+        Err(e) => return stream::once(future::ready(Err(e))).left_stream(),
+    };
+    let (checkpoint, query) = (Arc::new(checkpoint), Arc::new(query));
+    let succeeded = Arc::new(AtomicUsize::new(finished.len()));
+    let failed = Arc::new(AtomicUsize::new(0));
+    let (ok, err) = (succeeded.clone(), failed.clone());
+
+    let queried = stream::iter(pending)
+        .map(move |(_, word)| {
+            let (checkpoint, query) = (checkpoint.clone(), query.clone());
+            async move {
+                let (_, ret, _) =
+                    query_word(options, checkpoint.as_ref().as_ref(), word, &*query).await;
+                ret
+            }
+        })
+        .buffer_unordered(options.concurrency.max(1))
+        .take_until(options.stopped())
+        .inspect(move |r| {
+            let counter = if r.is_ok() { &ok } else { &err };
+            counter.fetch_add(1, Ordering::Relaxed);
+        });
+    let end = stream::once(async move {
+        options.emit(ProgressEvent::BatchFinished {
+            succeeded: succeeded.load(Ordering::Relaxed),
+            failed: failed.load(Ordering::Relaxed),
+        });
+        None
+    })
+    .filter_map(future::ready);
+
+    stream::iter(
+        finished
+            .into_iter()
+            .filter_map(BatchItem::into_entry)
+            .map(Ok),
+    )
+    .chain(queried)
+    .chain(end)
+    .right_stream()
+}
+
+#[test]
+fn test_resume_from_checkpoint() {
+    use std::cell::RefCell;
+    use tokio::runtime::Runtime;
+    let path = std::env::temp_dir().join(format!("crawlins-checkpoint-{}", std::process::id()));
+    let _ = fs::remove_file(&path);
+    let mut options = BatchOptions::new();
+    options.set_checkpoint(Some(&path));
+    options.set_retry(RetryPolicy::none());
+    let words = ["a", "b", "c"].map(String::from);
+    let queried = RefCell::new(vec![]);
+    let url = |w: &str| Ok(format!("http://127.0.0.1/{}", w));
+    let rt = Runtime::new().unwrap();
+
+    // first run is killed after "a" is finished
+    let ret: Vec<String> = rt
+        .block_on(run(&options, &words[..1], url, |w| {
+            queried.borrow_mut().push(w.clone());
+            async move { Ok(w.to_uppercase()) }
+        }))
+        .unwrap()
+        .into_entries();
+    assert_eq!(ret, ["A"]);
+    // simulate a line cut off when process was killed
+    fs::OpenOptions::new()
+        .append(true)
+        .open(&path)
+        .unwrap()
+        .write_all(br#"{"word":"b","ent"#)
+        .unwrap();
+
+    let ret: Vec<String> = rt
+        .block_on(run(&options, &words, url, |w| {
+            queried.borrow_mut().push(w.clone());
+            async move { Ok(w.to_uppercase()) }
+        }))
+        .unwrap()
+        .into_entries();
+    assert_eq!(ret, ["A", "B", "C"]);
+    let mut queried = queried.into_inner();
+    queried.sort();
+    assert_eq!(queried, ["a", "b", "c"]);
+    // a new line is started only after the cut off one
+    let content = fs::read_to_string(&path).unwrap();
+    assert_eq!(content.lines().filter(|l| l.is_empty()).count(), 0);
+
+    fs::remove_file(path).unwrap();
+}
+
+#[test]
+fn test_failure_report() {
+    use tokio::runtime::Runtime;
+    let path = std::env::temp_dir().join(format!("crawlins-report-{}", std::process::id()));
+    let mut options = BatchOptions::new();
+    options.set_report(Some(&path));
+    let mut retry = RetryPolicy::new();
+    retry.set_base_delay(Duration::ZERO);
+    options.set_retry(retry);
+    let (tx, mut rx) = tokio::sync::mpsc::unbounded_channel();
+    options.set_progress(Some(ProgressHandler::from_sender(tx)));
+    let words = ["ok", "gone", "busy"].map(String::from);
+    let rt = Runtime::new().unwrap();
+
+    let output = rt
+        .block_on(run(
+            &options,
+            &words,
+            |w| Ok(format!("http://127.0.0.1/{}", w)),
+            |w| async move {
+                let status = match w.as_str() {
+                    "gone" => 404,
+                    "busy" => 503,
+                    _ => return Ok(w),
+                };
+                Err(Error::from_status(status, Default::default(), None))
+            },
+        ))
+        .unwrap();
+    assert_eq!(output.entries().collect::<Vec<_>>(), ["ok"]);
+    assert!(output.report_error().is_none());
+
+    let report = FailureReport::load(&path).unwrap();
+    assert_eq!(&report, output.report());
+    let f = &report.failures()[0];
+    assert_eq!(
+        (f.index(), f.word(), f.kind(), f.status(), f.attempts()),
+        (1, "gone", "not_found", Some(404), 1)
+    );
+    let f = &report.failures()[1];
+    assert_eq!(
+        (f.index(), f.word(), f.url(), f.kind(), f.attempts()),
+        (2, "busy", "http://127.0.0.1/busy", "http_status", 3)
+    );
+
+    drop(options);
+    let mut events = vec![];
+    while let Some(e) = rx.blocking_recv() {
+        events.push(e);
+    }
+    let retried = events
+        .iter()
+        .filter(|e| matches!(e, ProgressEvent::Retried { word, .. } if word == "busy"))
+        .count();
+    assert_eq!(retried, 2);
+    assert_eq!(
+        events.last(),
+        Some(&ProgressEvent::BatchFinished {
+            succeeded: 1,
+            failed: 2
+        })
+    );
+
+    fs::remove_file(&path).unwrap();
+
+    // report file in a missing directory
+    let mut options = BatchOptions::new();
+    options.set_report(Some(path.join("report.json")));
+    let output = rt
+        .block_on(run(
+            &options,
+            &words,
+            |w| Ok(w.into()),
+            |w| async move { Ok(w) },
+        ))
+        .unwrap();
+    assert_eq!(output.entries().count(), 3);
+    assert_eq!(output.report_error().map(Error::kind), Some("io"));
+}
+
+#[test]
+fn test_stream() {
+    use tokio::runtime::Runtime;
+    let mut options = BatchOptions::new();
+    options.set_retry(RetryPolicy::none());
+    options.set_concurrency(2);
+    let (tx, mut rx) = tokio::sync::mpsc::unbounded_channel();
+    options.set_progress(Some(ProgressHandler::from_sender(tx)));
+    let words = ["a", "bad", "c"].map(String::from);
+    let rt = Runtime::new().unwrap();
+
+    let ret: Vec<Result<String>> = rt.block_on(
+        stream(&options, &words, |w| async move {
+            if w == "bad" {
+                Err(Error::Download("bad".into()))
+            } else {
+                Ok(w.to_uppercase())
+            }
+        })
+        .collect(),
+    );
+    let mut entries = ret
+        .iter()
+        .filter_map(|r| r.as_ref().ok())
+        .collect::<Vec<_>>();
+    entries.sort();
+    assert_eq!(entries, ["A", "C"]);
+    assert_eq!(ret.iter().filter(|r| r.is_err()).count(), 1);
+
+    drop(options);
+    let mut last = None;
+    while let Some(e) = rx.blocking_recv() {
+        last = Some(e);
+    }
+    assert_eq!(
+        last,
+        Some(ProgressEvent::BatchFinished {
+            succeeded: 2,
+            failed: 1
+        })
+    );
+}
+
+#[test]
+fn test_input_order() {
+    use tokio::runtime::Runtime;
+    let mut options = BatchOptions::new();
+    options.set_retry(RetryPolicy::none());
+    let words = ["slow", "bad", "fast"].map(String::from);
+    let rt = Runtime::new().unwrap();
+
+    let output = rt
+        .block_on(run(
+            &options,
+            &words,
+            |w| Ok(w.into()),
+            |w| async move {
+                let delay = match w.as_str() {
+                    "slow" => 50,
+                    "bad" => return Err(Error::Download(w)),
+                    _ => 0,
+                };
+                tokio::time::sleep(Duration::from_millis(delay)).await;
+                Ok(w)
+            },
+        ))
+        .unwrap();
+    let items = output
+        .items()
+        .iter()
+        .map(|i| (i.index(), i.word(), i.entry().map(String::as_str)))
+        .collect::<Vec<_>>();
+    assert_eq!(
+        items,
+        [
+            (0, "slow", Some("slow")),
+            (1, "bad", None),
+            (2, "fast", Some("fast"))
+        ]
+    );
+    assert_eq!(output.into_entries(), ["slow", "fast"]);
+}
+
+#[test]
+fn test_cancellation() {
+    use tokio::runtime::Runtime;
+    let token = CancellationToken::new();
+    let mut options = BatchOptions::new();
+    options.set_concurrency(1);
+    options.set_cancellation(Some(token.clone()));
+    let words = ["a", "b", "c"].map(String::from);
+    let rt = Runtime::new().unwrap();
+
+    // "b" cancels the batch while it is running,"c" is never started
+    let output = rt
+        .block_on(run(
+            &options,
+            &words,
+            |w| Ok(w.into()),
+            |w| {
+                let token = token.clone();
+                async move {
+                    if w == "b" {
+                        token.cancel();
+                        future::pending::<()>().await;
+                    }
+                    Ok(w)
+                }
+            },
+        ))
+        .unwrap();
+    assert_eq!(output.entries().collect::<Vec<_>>(), ["a"]);
+    let failures = output
+        .report()
+        .failures()
+        .iter()
+        .map(|f| (f.index(), f.kind(), f.attempts()))
+        .collect::<Vec<_>>();
+    assert_eq!(failures, [(1, "cancelled", 0), (2, "cancelled", 0)]);
+
+    // deadline stops a batch the same way
+    let mut options = BatchOptions::new();
+    options.set_deadline(Some(Duration::from_millis(50)));
+    let output = rt
+        .block_on(run(
+            &options,
+            &words,
+            |w| Ok(w.into()),
+            |w| async move {
+                if w == "c" {
+                    future::pending::<()>().await;
+                }
+                Ok(w)
+            },
+        ))
+        .unwrap();
+    assert_eq!(output.into_entries(), ["a", "b"]);
+}
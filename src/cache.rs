@@ -0,0 +1,135 @@
+//! keep fetched pages on disk,so that a batch can be parsed again after a parser
+//! changes without downloading everything again.
+//!
+//! every page is saved as one file named after hash of its url.
+use crate::error::{Error, Result};
+use std::{
+    path::{Path, PathBuf},
+    time::Duration,
+};
+
+/// how [`ResponseCache`] is used by a fetcher
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum CacheMode {
+    /// serve fresh pages from cache,fetch and save the others
+    #[default]
+    ReadWrite,
+    /// never touch network,a page missing from cache is an error
+    Offline,
+    /// always fetch and overwrite cached pages
+    Refresh,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ResponseCache {
+    dir: PathBuf,
+    /// pages older than it are fetched again,`None` means pages never expire
+    ttl: Option<Duration>,
+    mode: CacheMode,
+}
+
+impl ResponseCache {
+    pub fn new<P: Into<PathBuf>>(dir: P) -> Self {
+        Self {
+            dir: dir.into(),
+            ttl: None,
+            mode: CacheMode::default(),
+        }
+    }
+
+    pub fn set_ttl(&mut self, ttl: Option<Duration>) {
+        self.ttl = ttl;
+    }
+
+    pub fn set_mode(&mut self, mode: CacheMode) {
+        self.mode = mode;
+    }
+
+    pub fn dir(&self) -> &Path {
+        self.dir.as_ref()
+    }
+
+    pub fn ttl(&self) -> Option<Duration> {
+        self.ttl
+    }
+
+    pub fn mode(&self) -> CacheMode {
+        self.mode
+    }
+
+    /// file holding page of `url`
+    pub fn path(&self, url: &str) -> PathBuf {
+        self.dir
+            .join(format!("{:016x}.html", fnv1a(url.as_bytes())))
+    }
+
+    /// cached page of `url` if it is present and not expired.
+    ///
+    /// in offline mode a missing page is an error.
+    pub(crate) async fn get(&self, url: &str) -> Result<Option<String>> {
+        if self.mode == CacheMode::Refresh {
+            return Ok(None);
+        }
+        let path = self.path(url);
+        let fresh = match tokio::fs::metadata(&path).await {
+            Ok(meta) => match self.ttl {
+                Some(ttl) => meta
+                    .modified()?
+                    .elapsed()
+                    .map_or(true, |elapsed| elapsed <= ttl),
+                None => true,
+            },
+            Err(_) => false,
+        };
+        if fresh {
+            Ok(Some(tokio::fs::read_to_string(&path).await?))
+        } else if self.mode == CacheMode::Offline {
+            Err(Error::CacheMiss(url.into()))
+        } else {
+            Ok(None)
+        }
+    }
+
+    pub(crate) async fn put(&self, url: &str, body: &str) -> Result<()> {
+        tokio::fs::create_dir_all(&self.dir).await?;
+        tokio::fs::write(self.path(url), body).await?;
+        Ok(())
+    }
+}
+
+/// 64-bit FNV-1a,stable between builds unlike hasher of std.
+fn fnv1a(bytes: &[u8]) -> u64 {
+    bytes.iter().fold(0xcbf29ce484222325, |hash, b| {
+        (hash ^ u64::from(*b)).wrapping_mul(0x100000001b3)
+    })
+}
+
+#[test]
+fn test_cache() {
+    use tokio::runtime::Runtime;
+    let dir = std::env::temp_dir().join(format!("crawlins-cache-{}", std::process::id()));
+    let url = "https://www.zdic.net/hans/%E6%AC%B2";
+    let rt = Runtime::new().unwrap();
+    let mut cache = ResponseCache::new(&dir);
+
+    assert!(rt.block_on(cache.get(url)).unwrap().is_none());
+    rt.block_on(cache.put(url, "<html></html>")).unwrap();
+    assert_eq!(
+        rt.block_on(cache.get(url)).unwrap().as_deref(),
+        Some("<html></html>")
+    );
+
+    // expired pages are fetched again
+    cache.set_ttl(Some(Duration::ZERO));
+    std::thread::sleep(Duration::from_millis(10));
+    assert!(rt.block_on(cache.get(url)).unwrap().is_none());
+
+    // offline mode never falls back to network
+    cache.set_mode(CacheMode::Offline);
+    assert!(matches!(
+        rt.block_on(cache.get(url)),
+        Err(Error::CacheMiss(_))
+    ));
+
+    std::fs::remove_dir_all(dir).unwrap();
+}
@@ -0,0 +1,134 @@
+//! decode bodies of pages into text.
+//!
+//! older chinese pages are often served as gbk/gb18030,sometimes with a header which
+//! disagrees with the page,so a byte order mark always wins,then a body which is valid
+//! utf-8 is taken as utf-8,since text in any other multibyte charset hardly ever is.
+//! otherwise charsets of `Content-Type` header and `<meta>` tag of the page are tried
+//! in order,and the first one which decodes the whole body without malformed bytes wins.
+use crate::error::{Context, Error, Result};
+use encoding_rs::{Encoding, UTF_8};
+
+/// how many bytes at the start of a page are searched for a `<meta>` charset
+const META_SCAN_LEN: usize = 1024;
+
+/// decode `body` of `url`,see module docs for how charset is chosen.
+///
+/// # Errors
+///
+/// [`Error::Misdecoded`] if no charset decodes the body cleanly,rather than passing
+/// mojibake to parsers.
+pub(crate) fn decode(body: &[u8], content_type: Option<&str>, url: &str) -> Result<String> {
+    if let Some((encoding, len)) = Encoding::for_bom(body) {
+        return decode_with(encoding, &body[len..]).ok_or_else(|| misdecoded(encoding, url));
+    }
+    let mut candidates = vec![];
+    for encoding in [
+        content_type.and_then(charset_of_content_type),
+        charset_of_meta(body),
+    ]
+    .into_iter()
+    .flatten()
+    {
+        if !candidates.contains(&encoding) {
+            candidates.push(encoding);
+        }
+    }
+    if candidates.iter().all(|e| e.is_ascii_compatible()) {
+        if let Ok(text) = std::str::from_utf8(body) {
+            return Ok(text.into());
+        }
+    }
+    candidates
+        .iter()
+        .find_map(|encoding| decode_with(encoding, body))
+        .ok_or_else(|| misdecoded(candidates.first().copied().unwrap_or(UTF_8), url))
+}
+
+/// `None` if `body` has malformed bytes in `encoding`
+fn decode_with(encoding: &'static Encoding, body: &[u8]) -> Option<String> {
+    encoding
+        .decode_without_bom_handling_and_without_replacement(body)
+        .map(|text| text.into_owned())
+}
+
+fn misdecoded(encoding: &'static Encoding, url: &str) -> Error {
+    Error::Misdecoded {
+        context: Context::from_url(url),
+        charset: encoding.name().into(),
+    }
+}
+
+/// charset parameter of a `Content-Type` value,e.g. `text/html; charset=GBK`
+fn charset_of_content_type(content_type: &str) -> Option<&'static Encoding> {
+    content_type
+        .split(';')
+        .skip(1)
+        .filter_map(|param| param.split_once('='))
+        .find(|(name, _)| name.trim().eq_ignore_ascii_case("charset"))
+        .and_then(|(_, value)| Encoding::for_label(value.trim().trim_matches('"').as_bytes()))
+}
+
+/// charset of `<meta charset="gbk">` or
+/// `<meta http-equiv="Content-Type" content="text/html; charset=gb2312">`
+fn charset_of_meta(body: &[u8]) -> Option<&'static Encoding> {
+    let head = body[..body.len().min(META_SCAN_LEN)].to_ascii_lowercase();
+    let head = String::from_utf8_lossy(&head);
+    head.split("<meta").skip(1).find_map(|tag| {
+        let tag = &tag[..tag.find('>').unwrap_or(tag.len())];
+        let start = tag.find("charset")? + "charset".len();
+        let value = tag[start..]
+            .trim_start()
+            .strip_prefix('=')?
+            .trim_start()
+            .trim_start_matches(['"', '\'']);
+        let end = value
+            .find(|c: char| c == '"' || c == '\'' || c == ';' || c == '/' || c.is_whitespace())
+            .unwrap_or(value.len());
+        Encoding::for_label(&value.as_bytes()[..end])
+    })
+}
+
+#[test]
+fn test_decode() {
+    use encoding_rs::GBK;
+    let url = "https://www.zdic.net/hans/欲盖弥彰";
+    let page = "<html><head><meta charset=\"gb2312\"></head><p>欲盖弥彰</p></html>";
+    let (gbk, _, _) = GBK.encode(page);
+
+    // from header,and from meta tag when header has no charset
+    assert_eq!(
+        decode(&gbk, Some("text/html; charset=GBK"), url).unwrap(),
+        page
+    );
+    assert_eq!(decode(&gbk, Some("text/html"), url).unwrap(), page);
+    // utf-8 page with a wrong header
+    assert_eq!(
+        decode("欲盖弥彰".as_bytes(), Some("text/html; charset=gbk"), url).unwrap(),
+        "欲盖弥彰"
+    );
+    // bom wins over everything
+    let mut bom = b"\xEF\xBB\xBF".to_vec();
+    bom.extend_from_slice("总而言之".as_bytes());
+    assert_eq!(
+        decode(&bom, Some("text/html; charset=gbk"), url).unwrap(),
+        "总而言之"
+    );
+    // gbk page without any declaration
+    match decode(&GBK.encode("总而言之").0, None, url) {
+        Err(e @ Error::Misdecoded { .. }) => {
+            assert_eq!(e.to_string(), format!("Misdecoded error UTF-8 {}", url))
+        }
+        ret => panic!("unexpected {:?}", ret),
+    }
+}
+
+#[test]
+fn test_charset_of_meta() {
+    let meta = br#"<meta http-equiv="Content-Type" content="text/html; charset=gb2312" />"#;
+    assert_eq!(charset_of_meta(meta), Some(encoding_rs::GBK));
+    assert_eq!(
+        charset_of_meta(b"<META CHARSET=gb18030>"),
+        Some(encoding_rs::GB18030)
+    );
+    assert_eq!(charset_of_meta(b"<meta name=\"charset\">"), None);
+}
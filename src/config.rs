@@ -0,0 +1,77 @@
+//! settings of one site,so that a crawl can be pointed at a mirror,a caching proxy
+//! or a local fixture server instead of the real site.
+use crate::{
+    error::Result,
+    ratelimit::RateLimit,
+    utils::{to_url_code, PC},
+};
+use std::time::Duration;
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct SourceConfig {
+    /// the word to query is appended to it directly,so it usually ends with `/`
+    base_url: String,
+    user_agent: String,
+    /// timeout of a whole request,`None` means no timeout
+    timeout: Option<Duration>,
+    /// requests per second allowed to host of `base_url`,`None` means no limit
+    rate_limit: Option<RateLimit>,
+}
+
+impl SourceConfig {
+    /// create config with default user agent and rate limit,and no timeout
+    pub fn new<S: Into<String>>(base_url: S) -> Self {
+        Self {
+            base_url: base_url.into(),
+            user_agent: PC.into(),
+            timeout: None,
+            rate_limit: Some(RateLimit::default()),
+        }
+    }
+
+    pub fn set_base_url<S: Into<String>>(&mut self, base_url: S) {
+        self.base_url = base_url.into();
+    }
+
+    pub fn set_user_agent<S: Into<String>>(&mut self, user_agent: S) {
+        self.user_agent = user_agent.into();
+    }
+
+    pub fn set_timeout(&mut self, timeout: Option<Duration>) {
+        self.timeout = timeout;
+    }
+
+    pub fn set_rate_limit(&mut self, rate_limit: Option<RateLimit>) {
+        self.rate_limit = rate_limit;
+    }
+
+    pub fn base_url(&self) -> &str {
+        self.base_url.as_ref()
+    }
+
+    pub fn user_agent(&self) -> &str {
+        self.user_agent.as_ref()
+    }
+
+    pub fn timeout(&self) -> Option<Duration> {
+        self.timeout
+    }
+
+    pub fn rate_limit(&self) -> Option<&RateLimit> {
+        self.rate_limit.as_ref()
+    }
+
+    /// url of the page of `word`,already transformed to uri code.
+    pub(crate) fn entry_url(&self, word: &str) -> Result<String> {
+        to_url_code(format!("{}{}", self.base_url, word))
+    }
+}
+
+#[test]
+fn test_entry_url() {
+    let config = SourceConfig::new("http://127.0.0.1:8080/hans/");
+    assert_eq!(
+        config.entry_url("我们").unwrap(),
+        "http://127.0.0.1:8080/hans/%E6%88%91%E4%BB%AC"
+    );
+}
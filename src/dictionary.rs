@@ -0,0 +1,61 @@
+//! common interface of all supported sites.
+//!
+//! every site module exposes a type implementing [`Dictionary`],so downstream code
+//! can be written once and be generic over any source.
+//!
+//! # example
+//! ```ignore
+//! async fn dump<D: Dictionary>(dict: &D, words: &[String], fpath: &str) -> Result<()> {
+//!     let entries = dict.query_batch(words).await?;
+//!     std::fs::write(fpath, serde_json::to_string(&entries)?)?;
+//!     Ok(())
+//! }
+//!
+//! async fn print<D: Dictionary>(dict: &D, words: &[String]) where D::Entry: Debug {
+//!     let mut entries = pin!(dict.query_stream(words));
+//!     while let Some(entry) = entries.next().await {
+//!         println!("{:?}", entry);
+//!     }
+//! }
+//! ```
+use crate::{batch::BatchOutput, error::Result};
+use futures::Stream;
+use serde::{de::DeserializeOwned, Serialize};
+use std::future::Future;
+
+pub trait Dictionary: Send + Sync {
+    /// what a successful query of one word produces.
+    type Entry: Serialize + DeserializeOwned + Clone + Send + 'static;
+    /// identifier of the site,e.g. `zdic.net`
+    const SOURCE: &'static str;
+
+    /// query only one word
+    fn query_one(&self, word: &str) -> impl Future<Output = Result<Self::Entry>> + Send;
+
+    /// query more than one word
+    ///
+    /// return entries together with a report of words which fail to be queried.
+    fn query_batch_report(
+        &self,
+        words: &[String],
+    ) -> impl Future<Output = Result<BatchOutput<Self::Entry>>> + Send;
+
+    /// query more than one word
+    ///
+    /// words which fail to be queried are not included in return value.
+    fn query_batch(
+        &self,
+        words: &[String],
+    ) -> impl Future<Output = Result<Vec<Self::Entry>>> + Send {
+        async move { Ok(self.query_batch_report(words).await?.into_entries()) }
+    }
+
+    /// query more than one word and yield every entry as soon as it is done
+    ///
+    /// entries come in completion order,a word failing after all retries is yielded
+    /// as an error.
+    fn query_stream<'a>(
+        &'a self,
+        words: &[String],
+    ) -> impl Stream<Item = Result<Self::Entry>> + Send + 'a;
+}
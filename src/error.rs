@@ -1,4 +1,5 @@
-use std::{num, result};
+use serde::{Deserialize, Serialize};
+use std::{fmt, num, result, time::Duration};
 pub type Result<T> = result::Result<T, Error>;
 #[derive(thiserror::Error, Debug)]
 pub enum Error {
@@ -8,14 +9,270 @@ pub enum Error {
     ParseInt(#[from] num::ParseIntError),
     #[error("Reqwest error: {0}")]
     Reqwest(#[from] reqwest::Error),
-    #[error("JsonParse error {0}")]
+    #[error("Join error {0}")]
     Tokio(#[from] tokio::task::JoinError),
     #[error("JsonParse error {0}")]
     SerdeJson(#[from] serde_json::Error),
     #[error("Download error {0}")]
     Download(String),
+    /// a css selector could not be parsed
     #[error("ParseHtmlSelector error {0}")]
     ParseHtmlSelector(String),
     #[error("UrlTransform error {0}")]
     UrlTransform(String),
+    /// page is not in cache while fetcher is offline
+    #[error("CacheMiss error {0}")]
+    CacheMiss(String),
+    /// no fixture file of the url while fixtures are replayed
+    #[error("MissingFixture error {0}")]
+    MissingFixture(String),
+    /// no data was received from server within read timeout
+    #[error("ReadTimeout error {0}")]
+    ReadTimeout(String),
+    /// batch was cancelled or its deadline passed before the word was done
+    #[error("Cancelled error {0}")]
+    Cancelled(String),
+    /// robots.txt of the site does not allow the url to be crawled
+    #[error("Disallowed error {0}")]
+    Disallowed(String),
+    /// site has no entry of the word,either answered 404 or a page without entry
+    #[error("NotFound error {0}")]
+    NotFound(Context),
+    /// server answered 429
+    #[error("RateLimited error {context}")]
+    RateLimited {
+        context: Context,
+        /// value of header `Retry-After` if present
+        retry_after: Option<Duration>,
+    },
+    /// body of the page is not valid in its charset,or in any charset it declares
+    #[error("Misdecoded error {charset} {context}")]
+    Misdecoded { context: Context, charset: String },
+    /// server refused us,e.g. 403
+    #[error("Blocked error {status} {context}")]
+    Blocked { context: Context, status: u16 },
+    /// page of the word was found but lacks an element every entry should have
+    #[error("MissingField error {field} {context}")]
+    MissingField { context: Context, field: String },
+    /// server answered with any other status than 2xx
+    #[error("HttpStatus error {status} {context}")]
+    HttpStatus {
+        context: Context,
+        status: u16,
+        /// value of header `Retry-After` if present
+        retry_after: Option<Duration>,
+    },
+}
+
+/// where an error happened,empty fields are unknown and left out of display.
+///
+/// fetchers only know the url,sites fill in source and word.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Context {
+    source: String,
+    word: String,
+    url: String,
+}
+
+impl Context {
+    pub fn new<S, W, U>(source: S, word: W, url: U) -> Self
+    where
+        S: Into<String>,
+        W: Into<String>,
+        U: Into<String>,
+    {
+        Self {
+            source: source.into(),
+            word: word.into(),
+            url: url.into(),
+        }
+    }
+
+    /// context of which only url is known
+    pub fn from_url<U: Into<String>>(url: U) -> Self {
+        Self {
+            url: url.into(),
+            ..Default::default()
+        }
+    }
+
+    pub fn source(&self) -> &str {
+        self.source.as_ref()
+    }
+
+    pub fn word(&self) -> &str {
+        self.word.as_ref()
+    }
+
+    pub fn url(&self) -> &str {
+        self.url.as_ref()
+    }
+}
+
+impl fmt::Display for Context {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let mut parts = vec![];
+        if !self.source.is_empty() {
+            parts.push(format!("[{}]", self.source));
+        }
+        parts.extend(
+            [&self.word, &self.url]
+                .into_iter()
+                .filter(|s| !s.is_empty())
+                .cloned(),
+        );
+        write!(f, "{}", parts.join(" "))
+    }
+}
+
+impl Error {
+    /// error of a response with a status other than 2xx
+    pub fn from_status(status: u16, context: Context, retry_after: Option<Duration>) -> Self {
+        match status {
+            404 | 410 => Error::NotFound(context),
+            429 => Error::RateLimited {
+                context,
+                retry_after,
+            },
+            401 | 403 | 451 => Error::Blocked { context, status },
+            _ => Error::HttpStatus {
+                context,
+                status,
+                retry_after,
+            },
+        }
+    }
+
+    /// error of a page without `field`,fill in context with [`Error::with_context`]
+    pub fn missing_field<F: Into<String>>(field: F) -> Self {
+        Error::MissingField {
+            context: Context::default(),
+            field: field.into(),
+        }
+    }
+
+    /// whether the same request may succeed if it is sent again.
+    ///
+    /// timeouts,broken connections,5xx and 429 are transient,while something like
+    /// 404 or a missing element will fail the same way next time.
+    pub fn is_retryable(&self) -> bool {
+        match self {
+            Error::Reqwest(e) => e.is_timeout() || e.is_connect() || e.is_request() || e.is_body(),
+            Error::ReadTimeout(_) => true,
+            Error::RateLimited { .. } => true,
+            Error::HttpStatus { status, .. } => *status == 408 || (500..600).contains(status),
+            _ => false,
+        }
+    }
+
+    /// short stable name of error kind,used in failure reports.
+    pub fn kind(&self) -> &'static str {
+        match self {
+            Error::IO(_) => "io",
+            Error::ParseInt(_) => "parse_int",
+            Error::Reqwest(e) if e.is_timeout() => "timeout",
+            Error::Reqwest(e) if e.is_connect() => "connect",
+            Error::Reqwest(_) => "request",
+            Error::Tokio(_) => "join",
+            Error::SerdeJson(_) => "json",
+            Error::Download(_) => "download",
+            Error::ParseHtmlSelector(_) => "parse_html",
+            Error::UrlTransform(_) => "url",
+            Error::CacheMiss(_) => "cache_miss",
+            Error::MissingFixture(_) => "missing_fixture",
+            Error::ReadTimeout(_) => "timeout",
+            Error::Cancelled(_) => "cancelled",
+            Error::Disallowed(_) => "disallowed",
+            Error::NotFound(_) => "not_found",
+            Error::RateLimited { .. } => "rate_limited",
+            Error::Blocked { .. } => "blocked",
+            Error::MissingField { .. } => "missing_field",
+            Error::Misdecoded { .. } => "charset",
+            Error::HttpStatus { .. } => "http_status",
+        }
+    }
+
+    /// how long the server asked us to wait before sending request again.
+    pub fn retry_after(&self) -> Option<Duration> {
+        match self {
+            Error::RateLimited { retry_after, .. } | Error::HttpStatus { retry_after, .. } => {
+                *retry_after
+            }
+            _ => None,
+        }
+    }
+
+    /// source,word and url the error is about if known
+    pub fn context(&self) -> Option<&Context> {
+        match self {
+            Error::NotFound(context)
+            | Error::RateLimited { context, .. }
+            | Error::Blocked { context, .. }
+            | Error::MissingField { context, .. }
+            | Error::Misdecoded { context, .. }
+            | Error::HttpStatus { context, .. } => Some(context),
+            _ => None,
+        }
+    }
+
+    /// http status the error was made from
+    pub fn status(&self) -> Option<u16> {
+        match self {
+            Error::NotFound(_) => Some(404),
+            Error::RateLimited { .. } => Some(429),
+            Error::Blocked { status, .. } | Error::HttpStatus { status, .. } => Some(*status),
+            _ => None,
+        }
+    }
+
+    /// fill in fields of context which are still unknown
+    pub(crate) fn with_context(mut self, source: &str, word: &str, url: &str) -> Self {
+        let context = match &mut self {
+            Error::NotFound(context)
+            | Error::RateLimited { context, .. }
+            | Error::Blocked { context, .. }
+            | Error::MissingField { context, .. }
+            | Error::Misdecoded { context, .. }
+            | Error::HttpStatus { context, .. } => context,
+            _ => return self,
+        };
+        for (field, value) in [
+            (&mut context.source, source),
+            (&mut context.word, word),
+            (&mut context.url, url),
+        ] {
+            if field.is_empty() {
+                *field = value.into();
+            }
+        }
+        self
+    }
+}
+
+#[test]
+fn test_from_status() {
+    let context = Context::from_url("https://www.zdic.net/hans/x");
+    let e = Error::from_status(404, context.clone(), None).with_context("zdic.net", "x", "");
+    assert_eq!(e.kind(), "not_found");
+    assert_eq!(
+        e.context(),
+        Some(&Context::new(
+            "zdic.net",
+            "x",
+            "https://www.zdic.net/hans/x"
+        ))
+    );
+    assert_eq!(
+        e.to_string(),
+        "NotFound error [zdic.net] x https://www.zdic.net/hans/x"
+    );
+
+    let e = Error::from_status(429, context.clone(), Some(Duration::from_secs(1)));
+    assert!(e.is_retryable());
+    assert_eq!(e.retry_after(), Some(Duration::from_secs(1)));
+    assert_eq!(
+        Error::from_status(403, context.clone(), None).kind(),
+        "blocked"
+    );
+    assert!(Error::from_status(503, context, None).is_retryable());
 }
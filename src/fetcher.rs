@@ -0,0 +1,174 @@
+//! transport used by every site to get pages.
+//!
+//! parsers only need the body of a url,so a site takes any [`Fetcher`]: the pooled
+//! [`HttpFetcher`],a [`MemoryFetcher`] in tests,a [`DirFetcher`] reading saved pages,
+//! or anything else such as a headless browser.
+//!
+//! [`HttpFetcher`]: crate::HttpFetcher
+//! [`DirFetcher`]: crate::DirFetcher
+use crate::{
+    config::SourceConfig,
+    error::{Context, Error, Result},
+};
+use futures::Future;
+use std::{collections::HashMap, time::Duration};
+
+/// what a [`Fetcher`] got for a url
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Response {
+    status: u16,
+    /// names are lowercase
+    headers: Vec<(String, String)>,
+    body: String,
+}
+
+impl Response {
+    pub fn new<S: Into<String>>(status: u16, body: S) -> Self {
+        Self {
+            status,
+            headers: vec![],
+            body: body.into(),
+        }
+    }
+
+    /// a `200 OK` response
+    pub fn ok<S: Into<String>>(body: S) -> Self {
+        Self::new(200, body)
+    }
+
+    pub fn add_header<N: AsRef<str>, V: Into<String>>(&mut self, name: N, value: V) {
+        self.headers
+            .push((name.as_ref().to_ascii_lowercase(), value.into()));
+    }
+
+    pub fn status(&self) -> u16 {
+        self.status
+    }
+
+    pub fn headers(&self) -> &[(String, String)] {
+        self.headers.as_ref()
+    }
+
+    /// first value of header `name`,case insensitive
+    pub fn header(&self, name: &str) -> Option<&str> {
+        self.headers
+            .iter()
+            .find(|(n, _)| n.eq_ignore_ascii_case(name))
+            .map(|(_, v)| v.as_str())
+    }
+
+    pub fn body(&self) -> &str {
+        self.body.as_ref()
+    }
+
+    pub fn is_success(&self) -> bool {
+        (200..300).contains(&self.status)
+    }
+
+    /// value of header `Retry-After`,only delay in seconds is supported.
+    pub fn retry_after(&self) -> Option<Duration> {
+        self.header("retry-after")?
+            .trim()
+            .parse()
+            .ok()
+            .map(Duration::from_secs)
+    }
+
+    /// body of a 2xx response,any other status is classified by [`Error::from_status`].
+    pub fn into_text(self, url: &str) -> Result<String> {
+        if self.is_success() {
+            Ok(self.body)
+        } else {
+            Err(Error::from_status(
+                self.status,
+                Context::from_url(url),
+                self.retry_after(),
+            ))
+        }
+    }
+}
+
+pub trait Fetcher: Send + Sync {
+    /// get the page of `url`.
+    ///
+    /// a response with a status other than 2xx is not an error here,only failing to
+    /// get any response is.
+    fn fetch(
+        &self,
+        url: &str,
+        config: &SourceConfig,
+    ) -> impl Future<Output = Result<Response>> + Send;
+
+    /// body of the page of `url`,see [`Response::into_text`]
+    fn text(
+        &self,
+        url: &str,
+        config: &SourceConfig,
+    ) -> impl Future<Output = Result<String>> + Send {
+        async move { self.fetch(url, config).await?.into_text(url) }
+    }
+}
+
+/// serve responses put into it beforehand,any other url gets `404 Not Found`.
+#[derive(Debug, Clone, Default)]
+pub struct MemoryFetcher {
+    responses: HashMap<String, Response>,
+}
+
+impl MemoryFetcher {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn insert<S: Into<String>>(&mut self, url: S, response: Response) {
+        self.responses.insert(url.into(), response);
+    }
+
+    pub fn get(&self, url: &str) -> Option<&Response> {
+        self.responses.get(url)
+    }
+}
+
+impl Fetcher for MemoryFetcher {
+    async fn fetch(&self, url: &str, _: &SourceConfig) -> Result<Response> {
+        Ok(self
+            .responses
+            .get(url)
+            .cloned()
+            .unwrap_or_else(|| Response::new(404, "")))
+    }
+}
+
+#[test]
+fn test_memory_fetcher() {
+    use tokio::runtime::Runtime;
+    let config = SourceConfig::new("http://127.0.0.1/");
+    let mut fetcher = MemoryFetcher::new();
+    fetcher.insert("http://127.0.0.1/a", Response::ok("page a"));
+    let mut busy = Response::new(503, "");
+    busy.add_header("Retry-After", "3");
+    fetcher.insert("http://127.0.0.1/b", busy);
+    let rt = Runtime::new().unwrap();
+
+    assert_eq!(
+        rt.block_on(fetcher.text("http://127.0.0.1/a", &config))
+            .unwrap(),
+        "page a"
+    );
+    match rt.block_on(fetcher.text("http://127.0.0.1/b", &config)) {
+        Err(e @ Error::HttpStatus { status: 503, .. }) => {
+            assert_eq!(e.retry_after(), Some(Duration::from_secs(3)))
+        }
+        ret => panic!("unexpected {:?}", ret),
+    }
+    assert_eq!(
+        rt.block_on(fetcher.fetch("http://127.0.0.1/c", &config))
+            .unwrap()
+            .status(),
+        404
+    );
+    match rt.block_on(fetcher.text("http://127.0.0.1/c", &config)) {
+        Err(Error::NotFound(context)) => assert_eq!(context.url(), "http://127.0.0.1/c"),
+        ret => panic!("unexpected {:?}", ret),
+    }
+}
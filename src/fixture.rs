@@ -0,0 +1,187 @@
+//! record real responses into fixture files and serve them back,so that tests run
+//! without network.
+//!
+//! a page is saved under `{dir}/{host:port}/` with a file name made of its path and query,
+//! so fixtures can be checked in and reviewed like any other file,pages are saved as
+//! utf-8,but saved pages in any charset declared by their `<meta>` tag can be read.
+use crate::{
+    charset,
+    config::SourceConfig,
+    error::{Error, Result},
+    fetcher::{Fetcher, Response},
+};
+use reqwest::Url;
+use std::path::{Path, PathBuf};
+
+/// whether [`Fixtures`] are served or written
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum FixtureMode {
+    /// serve pages from fixture files,a missing one is an error and no request is sent
+    #[default]
+    Replay,
+    /// fetch pages from network and overwrite fixture files
+    Record,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Fixtures {
+    dir: PathBuf,
+    mode: FixtureMode,
+}
+
+impl Fixtures {
+    pub fn new<P: Into<PathBuf>>(dir: P, mode: FixtureMode) -> Self {
+        Self {
+            dir: dir.into(),
+            mode,
+        }
+    }
+
+    /// record if environment variable `CRAWLINS_RECORD` is set to `1`,replay otherwise
+    pub fn from_env<P: Into<PathBuf>>(dir: P) -> Self {
+        let mode = match std::env::var("CRAWLINS_RECORD").as_deref() {
+            Ok("1") => FixtureMode::Record,
+            _ => FixtureMode::Replay,
+        };
+        Self::new(dir, mode)
+    }
+
+    pub fn dir(&self) -> &Path {
+        self.dir.as_ref()
+    }
+
+    pub fn mode(&self) -> FixtureMode {
+        self.mode
+    }
+
+    /// file holding page of `url`.
+    ///
+    /// host with port and path with query are percent-encoded byte by byte,except for
+    /// letters,digits,`.` and `-`,so that no two urls share one file.
+    pub fn path(&self, url: &str) -> Result<PathBuf> {
+        let parsed = Url::parse(url).map_err(|e| Error::UrlTransform(format!("{} {}", url, e)))?;
+        let mut host = parsed.host_str().unwrap_or_default().to_string();
+        if let Some(port) = parsed.port() {
+            host = format!("{}:{}", host, port);
+        }
+        let mut name = parsed
+            .path()
+            .strip_prefix('/')
+            .unwrap_or(parsed.path())
+            .to_string();
+        if let Some(query) = parsed.query() {
+            name = format!("{}?{}", name, query);
+        }
+        Ok(self
+            .dir
+            .join(escape(&host))
+            .join(format!("{}.html", escape(&name))))
+    }
+
+    pub(crate) async fn get(&self, url: &str) -> Result<String> {
+        let path = self.path(url)?;
+        let body = tokio::fs::read(&path)
+            .await
+            .map_err(|_| Error::MissingFixture(url.into()))?;
+        charset::decode(&body, None, url)
+    }
+
+    pub(crate) async fn put(&self, url: &str, body: &str) -> Result<()> {
+        let path = self.path(url)?;
+        if let Some(dir) = path.parent() {
+            tokio::fs::create_dir_all(dir).await?;
+        }
+        tokio::fs::write(path, body).await?;
+        Ok(())
+    }
+}
+
+/// percent-encode every byte of `text` which is not safe in a file name
+fn escape(text: &str) -> String {
+    text.bytes()
+        .map(|b| match b {
+            b'a'..=b'z' | b'A'..=b'Z' | b'0'..=b'9' | b'.' | b'-' => (b as char).to_string(),
+            _ => format!("%{:02X}", b),
+        })
+        .collect()
+}
+
+/// [`Fetcher`] reading pages saved under a directory in the layout of [`Fixtures`],
+/// a url without file gets `404 Not Found`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DirFetcher {
+    fixtures: Fixtures,
+}
+
+impl DirFetcher {
+    pub fn new<P: Into<PathBuf>>(dir: P) -> Self {
+        Self {
+            fixtures: Fixtures::new(dir, FixtureMode::Replay),
+        }
+    }
+
+    pub fn dir(&self) -> &Path {
+        self.fixtures.dir()
+    }
+}
+
+impl Fetcher for DirFetcher {
+    async fn fetch(&self, url: &str, _: &SourceConfig) -> Result<Response> {
+        match self.fixtures.get(url).await {
+            Ok(body) => Ok(Response::ok(body)),
+            Err(Error::MissingFixture(_)) => Ok(Response::new(404, "")),
+            Err(e) => Err(e),
+        }
+    }
+}
+
+/// fetcher of tests,serving fixtures checked in under `fixtures/`.
+///
+/// run tests with `CRAWLINS_RECORD=1` to record them again from the real sites.
+#[cfg(test)]
+pub(crate) fn test_fetcher() -> crate::fetch::HttpFetcher {
+    let mut fetcher = crate::fetch::HttpFetcher::default();
+    fetcher.set_fixtures(Some(Fixtures::from_env(concat!(
+        env!("CARGO_MANIFEST_DIR"),
+        "/fixtures"
+    ))));
+    fetcher
+}
+
+#[test]
+fn test_path() {
+    let fixtures = Fixtures::new("fixtures", FixtureMode::Replay);
+    assert_eq!(
+        fixtures
+            .path("https://www.zdic.net/hans/%E6%AC%B2%E7%9B%96")
+            .unwrap(),
+        Path::new("fixtures/www.zdic.net/hans%2F%25E6%25AC%25B2%25E7%259B%2596.html")
+    );
+    assert_eq!(
+        fixtures.path("http://127.0.0.1:8080/a/b?c=d").unwrap(),
+        Path::new("fixtures/127.0.0.1%3A8080/a%2Fb%3Fc%3Dd.html")
+    );
+    // urls differing only in characters which used to be replaced
+    let paths = ["/a/b_c", "/a_b/c", "/a?b=c", "/a%2Fb"]
+        .map(|path| fixtures.path(&format!("http://127.0.0.1{}", path)).unwrap());
+    for (i, path) in paths.iter().enumerate() {
+        assert!(!paths[i + 1..].contains(path), "{:?}", path);
+    }
+}
+
+#[test]
+fn test_dir_fetcher() {
+    use tokio::runtime::Runtime;
+    let fetcher = DirFetcher::new(concat!(env!("CARGO_MANIFEST_DIR"), "/fixtures"));
+    let config = SourceConfig::new("https://www.merriam-webster.com/dictionary/");
+    let rt = Runtime::new().unwrap();
+
+    let page = rt
+        .block_on(fetcher.fetch(&config.entry_url("give").unwrap(), &config))
+        .unwrap();
+    assert!(page.is_success() && page.body().contains("give up"));
+    let missing = rt
+        .block_on(fetcher.fetch(&config.entry_url("take").unwrap(), &config))
+        .unwrap();
+    assert_eq!(missing.status(), 404);
+}
@@ -9,45 +9,106 @@
 //!
 //! # Analysis of entry web page.
 //! ## 拼音部分：
-//! ```
+//! ```text
 //! <span class="dicpy">yù gài mí zhāng</span>  
 //! ```
 //!
 //! ## 解释部分的元素块
-//! ```
+//! ```text
 //! <div class="content definitions cnr">
 //!  <h3>欲盖弥彰</h3><p>【解释】盖：遮掩；弥：更加；彰：明显。想掩盖坏事的真相，结果反而更明显地暴露出来。</p><p>【出处】《左传·昭公三十一年》：“或求名而不得，或欲盖而名章，惩不义也。”</p><p>【示例】与其～，倒不如自己先认了。 ◎闻一多《画展》</p><p>【反义词】相得益彰</p><p>【语法】紧缩式；作谓语、宾语、定语；含贬义</p>                    <div class="div copyright"> © 汉典 </div>
 //! </div>
 //! ```
+//!
+//! # Analysis of ordinary word (词语) web page.
+//! 拼音部分同上，释义是编号的义项，例句在义项里，多音的词语每个读音各有一个 `<ol>`，前面是该读音的 `dicpy`：
+//! ```text
+//! <div class="content definitions jnr">
+//!  <ol>
+//!   <li><p>彼此有交情的人。</p><p class="diczx1">例：他是我的老～。</p></li>
+//!   <li><p>指恋爱的对象。</p></li>
+//!  </ol>
+//! </div>
+//! <div class="jyfy">
+//!  <span class="jyc"><a href="/hans/友人">友人</a></span>
+//!  <span class="fyc"><a href="/hans/敌人">敌人</a></span>
+//! </div>
+//! ```
 
-use std::{fs, io::Write, sync::Arc};
+use std::{
+    collections::{BTreeMap, HashSet},
+    path::PathBuf,
+    time::Instant,
+};
 
 use crate::{
-    error::Result,
-    utils::{group_by_range, request_text, selector_parse_doc, selector_parse_frac},
+    batch::{self, BatchItem, BatchOutput, FailureReport},
+    config::SourceConfig,
+    dictionary::Dictionary,
+    error::{Context, Error, Result},
+    fetch::HttpFetcher,
+    fetcher::Fetcher,
+    pinyin::Reading,
+    site::{deref_site, Site},
+    utils::{parse_selector, selector_parse_doc, selector_parse_frac, text_of, texts},
 };
+use futures::Stream;
+use scraper::{ElementRef, Html};
 use serde::{Deserialize, Serialize};
 
 static PINYIN: &str = r#"span[class="dicpy"]"#;
 static DEFINITIONS: &str = r#"div[class="content definitions cnr"]"#;
 static PREFIX_URL: &str = "https://www.zdic.net/hans/";
-use futures::future::join_all;
-use tokio::sync::Mutex;
+// selectors of words follow the layout above,which is checked only against the
+// hand-written fixture of 朋友 until a recorded page replaces it,see `fixtures/README.md`
+/// senses are `li` of one `ol` per reading,each `ol` after `span.dicpy` of its reading
+static WORD_DEFINITIONS: &str = "div.definitions.jnr";
+static WORD_PINYIN: &str = "dicpy";
+static WORD_EXAMPLE: &str = "diczx1";
+static WORD_SYNONYMS: &str = ".jyfy .jyc a";
+static WORD_ANTONYMS: &str = ".jyfy .fyc a";
 /// It means handian chengyu.
 ///
 #[derive(Debug, Default, Serialize, Deserialize, Clone)]
 pub struct HanDianCY {
+    /// first reading as written on the page
     pinyin: String,
+    /// every reading of the entry,e.g. of a 多音字
+    #[serde(default)]
+    readings: Vec<Reading>,
     entry: String,
     /// 出处
     source: Option<String>,
+    /// 出处 split into quote and attribution
+    #[serde(default)]
+    source_citation: Option<Citation>,
     /// 解释
     meaning: Option<String>,
     example: Option<String>,
+    /// 示例 split into quote and attribution
+    #[serde(default)]
+    example_citation: Option<Citation>,
     /// 近义词
-    synonym: Option<String>,
+    #[serde(default)]
+    synonyms: Vec<String>,
     /// 反义词
-    antonym: Option<String>,
+    #[serde(default)]
+    antonyms: Vec<String>,
+    /// 语法
+    grammar: Option<String>,
+    /// 用法
+    usage: Option<String>,
+    /// 歇后语
+    xiehouyu: Option<String>,
+    /// 谜语
+    riddle: Option<String>,
+    /// 故事
+    story: Option<String>,
+    /// 英文翻译
+    english: Option<String>,
+    /// any other 【label】 and its text,so nothing on the page is lost
+    #[serde(default)]
+    others: BTreeMap<String, String>,
 }
 
 impl HanDianCY {
@@ -58,64 +119,90 @@ impl HanDianCY {
         }
     }
 
-    fn set_pinyin(&mut self, pinyin: String) {
-        self.pinyin = pinyin;
+    /// set readings from their texts,the first one is also kept as [`HanDianCY::pinyin`]
+    fn set_readings(&mut self, readings: Vec<String>) {
+        self.readings = readings.iter().map(|r| Reading::parse(r)).collect();
+        self.pinyin = readings.into_iter().next().unwrap_or_default();
     }
 
-    fn set_source(&mut self, source: Option<String>) {
-        self.source = source;
-    }
-
-    fn set_meaning(&mut self, meaning: Option<String>) {
-        self.meaning = meaning;
+    /// field of 【`label`】,`None` if it is kept in [`HanDianCY::others`]
+    fn field_mut(&mut self, label: &str) -> Option<&mut Option<String>> {
+        Some(match label {
+            "解释" => &mut self.meaning,
+            "出处" => &mut self.source,
+            "示例" => &mut self.example,
+            "语法" => &mut self.grammar,
+            "用法" => &mut self.usage,
+            "歇后语" => &mut self.xiehouyu,
+            "谜语" => &mut self.riddle,
+            "故事" => &mut self.story,
+            "英文" | "英译" | "英语" => &mut self.english,
+            _ => return None,
+        })
     }
 
-    fn set_example(&mut self, example: Option<String>) {
-        self.example = example;
+    /// set text of 【`label`】,or append it as a new line if the label is already set
+    fn push_field(&mut self, label: &str, text: &str) {
+        let list = match label {
+            "近义词" => Some(&mut self.synonyms),
+            "反义词" => Some(&mut self.antonyms),
+            _ => None,
+        };
+        if let Some(list) = list {
+            list.extend(split_words(text));
+            return;
+        }
+        let value = match self.field_mut(label) {
+            Some(field) => field.get_or_insert_with(String::new),
+            None => self.others.entry(label.into()).or_default(),
+        };
+        if !value.is_empty() {
+            value.push('\n');
+        }
+        value.push_str(text);
     }
 
     /// parse a block string to fields of [`HanDianCY`].
     ///
-    /// set its fields if that field is present in block str.
+    /// every line starts with a 【label】,a line without label continues the one before,
+    /// e.g. a story spanning several paragraphs.
     fn set_definitions(&mut self, block_str: Vec<String>) -> &mut Self {
+        let mut label = None;
         for b in block_str {
-            if b.contains("解释") {
-                let r = b.replace("【解释】", "");
-                self.set_meaning(Some(r));
-            }
-            if b.contains("【出处】") {
-                let r = b.replace("【出处】", "");
-                self.set_source(Some(r));
-            }
-            if b.contains("【示例】") {
-                let r = b.replace("【示例】", "");
-                self.set_example(Some(r));
-            }
-            if b.contains("【反义词】") {
-                let r = b.replace("【反义词】", "");
-                self.set_antonym(Some(r));
-            }
-            if b.contains("【近义词】") {
-                let r = b.replace("【近义词】", "");
-                self.set_synonym(Some(r));
+            let b = b.trim();
+            let text = match split_label(b) {
+                Some((l, text)) => {
+                    label = Some(l.to_string());
+                    text
+                }
+                None => b,
+            };
+            if let Some(label) = label.as_deref() {
+                if !text.is_empty() {
+                    self.push_field(label, text);
+                }
             }
         }
+        self.source_citation = self
+            .source
+            .as_deref()
+            .map(|s| Citation::parse_source(s, &self.entry));
+        self.example_citation = self
+            .example
+            .as_deref()
+            .map(|e| Citation::parse_example(e, &self.entry));
 
         self
     }
 
-    fn set_synonym(&mut self, synonym: Option<String>) {
-        self.synonym = synonym;
-    }
-
-    fn set_antonym(&mut self, antonym: Option<String>) {
-        self.antonym = antonym;
-    }
-
     pub fn pinyin(&self) -> &str {
         self.pinyin.as_ref()
     }
 
+    pub fn readings(&self) -> &[Reading] {
+        self.readings.as_ref()
+    }
+
     pub fn entry(&self) -> &str {
         self.entry.as_ref()
     }
@@ -128,32 +215,586 @@ impl HanDianCY {
         self.meaning.as_ref()
     }
 
-    pub fn synonym(&self) -> Option<&String> {
-        self.synonym.as_ref()
+    pub fn example(&self) -> Option<&String> {
+        self.example.as_ref()
+    }
+
+    pub fn source_citation(&self) -> Option<&Citation> {
+        self.source_citation.as_ref()
+    }
+
+    pub fn example_citation(&self) -> Option<&Citation> {
+        self.example_citation.as_ref()
+    }
+
+    pub fn synonyms(&self) -> &[String] {
+        self.synonyms.as_ref()
     }
 
-    pub fn antonym(&self) -> Option<&String> {
-        self.antonym.as_ref()
+    pub fn antonyms(&self) -> &[String] {
+        self.antonyms.as_ref()
+    }
+
+    pub fn grammar(&self) -> Option<&String> {
+        self.grammar.as_ref()
+    }
+
+    pub fn usage(&self) -> Option<&String> {
+        self.usage.as_ref()
+    }
+
+    pub fn xiehouyu(&self) -> Option<&String> {
+        self.xiehouyu.as_ref()
+    }
+
+    pub fn riddle(&self) -> Option<&String> {
+        self.riddle.as_ref()
+    }
+
+    pub fn story(&self) -> Option<&String> {
+        self.story.as_ref()
+    }
+
+    pub fn english(&self) -> Option<&String> {
+        self.english.as_ref()
+    }
+
+    /// 【label】s without a field of their own,label → text
+    pub fn others(&self) -> &BTreeMap<String, String> {
+        &self.others
+    }
+}
+/// a quote and where it comes from,parsed from 【出处】 or 【示例】
+#[derive(Debug, Default, Serialize, Deserialize, Clone, PartialEq, Eq)]
+pub struct Citation {
+    /// quote with `～` expanded to the entry,without quotation marks
+    quote: Option<String>,
+    /// e.g. 宋 or 南朝·宋
+    dynasty: Option<String>,
+    author: Option<String>,
+    /// title inside 《》
+    work: Option<String>,
+    /// chapter,after `·` inside 《》 or following it,e.g. 第五章
+    chapter: Option<String>,
+}
+
+impl Citation {
+    /// parse 出处 like `汉·司马迁《史记·项羽本纪》：“……”`
+    fn parse_source(text: &str, entry: &str) -> Self {
+        let mut citation = Self::default();
+        let rest = match split_work(text) {
+            Some((author, work, rest)) => {
+                citation.set_author(author);
+                citation.set_work(work);
+                // e.g. 》第五章：“……” or 》载：……
+                let (chapter, quote) = rest.split_once(['：', ':']).unwrap_or(("", rest));
+                let chapter = chapter.trim();
+                if citation.chapter.is_none()
+                    && !chapter.is_empty()
+                    && !matches!(chapter, "载" | "云" | "曰" | "说" | "中说" | "记载")
+                {
+                    citation.chapter = Some(chapter.into());
+                }
+                quote
+            }
+            None => text,
+        };
+        citation.set_quote(rest, entry);
+        citation
+    }
+
+    /// parse 示例 like `与其～，倒不如自己先认了。 ◎闻一多《画展》`
+    fn parse_example(text: &str, entry: &str) -> Self {
+        let mut citation = Self::default();
+        let (quote, attribution) = text.rsplit_once('◎').unwrap_or((text, ""));
+        citation.set_quote(quote, entry);
+        match split_work(attribution) {
+            Some((author, work, rest)) => {
+                citation.set_author(author);
+                citation.set_work(work);
+                let rest = rest.trim_matches(|c: char| c.is_whitespace() || "，。：".contains(c));
+                if citation.chapter.is_none() && !rest.is_empty() {
+                    citation.chapter = Some(rest.into());
+                }
+            }
+            None => citation.set_author(attribution),
+        }
+        citation
+    }
+
+    fn set_quote(&mut self, quote: &str, entry: &str) {
+        let quote = quote
+            .trim()
+            .trim_start_matches(['“', '"'])
+            .trim_end_matches(['”', '"'])
+            .trim()
+            .replace('～', entry);
+        if !quote.is_empty() {
+            self.quote = Some(quote);
+        }
+    }
+
+    /// `author` may start with dynasties,each of at most two characters,and lead-in
+    /// words like `语出` are not part of it
+    fn set_author(&mut self, author: &str) {
+        let author = author.trim();
+        let author = LEAD_INS
+            .iter()
+            .find_map(|w| author.strip_prefix(w))
+            .unwrap_or(author)
+            .trim();
+        // 见 alone is a lead-in,but may begin a name
+        let author = if author == "见" { "" } else { author };
+        // a long prefix is a description rather than a name,e.g. 十七世纪法国寓言诗人…的寓言
+        if author.is_empty() || author.chars().count() > 10 || author.contains('，') {
+            return;
+        }
+        match author.rsplit_once('·') {
+            Some((dynasty, name))
+                if dynasty.split('·').all(|d| d.chars().count() <= 2) && !name.is_empty() =>
+            {
+                self.dynasty = Some(dynasty.into());
+                self.author = Some(name.into());
+            }
+            _ => self.author = Some(author.into()),
+        }
+    }
+
+    /// `work` is what is inside 《》,e.g. 左传·昭公三十一年
+    fn set_work(&mut self, work: &str) {
+        let (work, chapter) = work.split_once('·').unwrap_or((work, ""));
+        self.work = Some(work.trim().into());
+        if !chapter.trim().is_empty() {
+            self.chapter = Some(chapter.trim().into());
+        }
+    }
+
+    pub fn quote(&self) -> Option<&String> {
+        self.quote.as_ref()
+    }
+
+    pub fn dynasty(&self) -> Option<&String> {
+        self.dynasty.as_ref()
+    }
+
+    pub fn author(&self) -> Option<&String> {
+        self.author.as_ref()
+    }
+
+    pub fn work(&self) -> Option<&String> {
+        self.work.as_ref()
+    }
+
+    pub fn chapter(&self) -> Option<&String> {
+        self.chapter.as_ref()
+    }
+}
+/// words leading in a source instead of naming its author,e.g. `语出《论语·学而》`
+static LEAD_INS: [&str; 7] = ["语出", "语本", "出自", "典出", "源出", "引自", "见于"];
+/// split `author《work》rest` at the first 《》
+fn split_work(text: &str) -> Option<(&str, &str, &str)> {
+    let (author, rest) = text.split_once('《')?;
+    let (work, rest) = rest.split_once('》')?;
+    Some((author, work, rest))
+}
+/// split a list of entries like `代人受过、为人作嫁`
+fn split_words(text: &str) -> impl Iterator<Item = String> + '_ {
+    text.split(|c: char| matches!(c, '、' | '，' | ',' | '；' | ';') || c.is_whitespace())
+        .filter(|w| !w.is_empty())
+        .map(String::from)
+}
+/// split `【label】text` into label and text
+fn split_label(line: &str) -> Option<(&str, &str)> {
+    let (label, text) = line.strip_prefix('【')?.split_once('】')?;
+    Some((label.trim(), text.trim()))
+}
+/// how two chengyu are related
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum RelationKind {
+    /// 近义词
+    Synonym,
+    /// 反义词
+    Antonym,
+}
+
+/// an edge of the relation graph of a [`HanDianCYCollection`]
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub struct Relation {
+    from: String,
+    to: String,
+    kind: RelationKind,
+    /// whether `to` is an entry of the collection too
+    resolved: bool,
+}
+
+impl Relation {
+    pub fn from(&self) -> &str {
+        self.from.as_ref()
+    }
+
+    pub fn to(&self) -> &str {
+        self.to.as_ref()
+    }
+
+    pub fn kind(&self) -> RelationKind {
+        self.kind
+    }
+
+    pub fn is_resolved(&self) -> bool {
+        self.resolved
     }
 }
 #[derive(Debug, Default, Serialize, Deserialize)]
 pub struct HanDianCYCollection {
+    /// entries which were asked for
     chengyucol: Vec<HanDianCY>,
+    /// synonyms and antonyms queried only to resolve relations
+    #[serde(default)]
+    linked: Vec<HanDianCY>,
+    /// synonyms and antonyms of every entry
+    #[serde(default)]
+    relations: Vec<Relation>,
+    /// entries which failed to be queried
+    #[serde(skip)]
+    report: FailureReport,
+    /// linked entries which failed to be queried,their relations stay unresolved
+    #[serde(skip)]
+    linked_report: FailureReport,
 }
 
 impl HanDianCYCollection {
-    fn new(chengyucol: Vec<HanDianCY>) -> Self {
-        Self { chengyucol }
+    fn new(chengyucol: Vec<HanDianCY>, linked: Vec<HanDianCY>) -> Self {
+        let all = || chengyucol.iter().chain(&linked);
+        let entries = all().map(|cy| cy.entry.as_str()).collect::<HashSet<_>>();
+        let relations = all()
+            .flat_map(|cy| {
+                let synonyms = cy.synonyms.iter().map(|s| (s, RelationKind::Synonym));
+                let antonyms = cy.antonyms.iter().map(|a| (a, RelationKind::Antonym));
+                synonyms.chain(antonyms).map(|(to, kind)| Relation {
+                    from: cy.entry.clone(),
+                    to: to.clone(),
+                    kind,
+                    resolved: entries.contains(to.as_str()),
+                })
+            })
+            .collect();
+        Self {
+            chengyucol,
+            linked,
+            relations,
+            ..Default::default()
+        }
     }
 
     pub fn chengyucol(&self) -> &[HanDianCY] {
         self.chengyucol.as_ref()
     }
+
+    pub fn linked(&self) -> &[HanDianCY] {
+        self.linked.as_ref()
+    }
+
+    pub fn relations(&self) -> &[Relation] {
+        self.relations.as_ref()
+    }
+
+    pub fn report(&self) -> &FailureReport {
+        &self.report
+    }
+
+    pub fn linked_report(&self) -> &FailureReport {
+        &self.linked_report
+    }
+
+    /// entry named `entry`,either asked for or linked
+    pub fn get(&self, entry: &str) -> Option<&HanDianCY> {
+        self.chengyucol
+            .iter()
+            .chain(&self.linked)
+            .find(|cy| cy.entry == entry)
+    }
+}
+/// one numbered sense of a [`HanDianWord`]
+#[derive(Debug, Default, Serialize, Deserialize, Clone, PartialEq, Eq)]
+pub struct Sense {
+    /// reading the sense belongs to as written on the page,e.g. `dì dào`
+    pinyin: String,
+    /// starts from 1 under every reading,as numbered on the page
+    number: usize,
+    definition: String,
+    examples: Vec<String>,
+}
+
+impl Sense {
+    pub fn pinyin(&self) -> &str {
+        self.pinyin.as_ref()
+    }
+
+    pub fn number(&self) -> usize {
+        self.number
+    }
+
+    pub fn definition(&self) -> &str {
+        self.definition.as_ref()
+    }
+
+    pub fn examples(&self) -> &[String] {
+        self.examples.as_ref()
+    }
+}
+/// It means handian ordinary word (词语),which is not a chengyu.
+#[derive(Debug, Default, Serialize, Deserialize, Clone)]
+pub struct HanDianWord {
+    /// first reading as written on the page
+    pinyin: String,
+    readings: Vec<Reading>,
+    entry: String,
+    senses: Vec<Sense>,
+    /// 近义词
+    synonyms: Vec<String>,
+    /// 反义词
+    antonyms: Vec<String>,
+}
+
+impl HanDianWord {
+    fn new(entry: String) -> Self {
+        Self {
+            entry,
+            ..Default::default()
+        }
+    }
+
+    pub fn pinyin(&self) -> &str {
+        self.pinyin.as_ref()
+    }
+
+    pub fn readings(&self) -> &[Reading] {
+        self.readings.as_ref()
+    }
+
+    pub fn entry(&self) -> &str {
+        self.entry.as_ref()
+    }
+
+    pub fn senses(&self) -> &[Sense] {
+        self.senses.as_ref()
+    }
+
+    pub fn synonyms(&self) -> &[String] {
+        self.synonyms.as_ref()
+    }
+
+    pub fn antonyms(&self) -> &[String] {
+        self.antonyms.as_ref()
+    }
+}
+/// [`Dictionary`] of chengyu from `https://www.zdic.net/`
+#[derive(Debug, Clone)]
+pub struct HanDian<F = HttpFetcher> {
+    site: Site<F>,
+    /// query synonyms and antonyms too when building a collection
+    resolve_relations: bool,
+}
+
+impl Default for HanDian {
+    fn default() -> Self {
+        Self {
+            site: Site::new(SourceConfig::new(PREFIX_URL)),
+            resolve_relations: false,
+        }
+    }
+}
+
+impl HanDian {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn with_config(config: SourceConfig) -> Self {
+        Self {
+            site: Site::new(config),
+            ..Default::default()
+        }
+    }
+}
+
+impl<F: Fetcher> HanDian<F> {
+    /// see [`Site::with_fetcher`]
+    pub fn with_fetcher(config: SourceConfig, fetcher: F) -> Self {
+        Self {
+            site: Site::with_fetcher(config, fetcher),
+            resolve_relations: false,
+        }
+    }
+
+    /// let [`HanDian::query_collection`] query every synonym and antonym not queried
+    /// already,so relations of the collection point at real entries
+    pub fn set_resolve_relations(&mut self, resolve_relations: bool) {
+        self.resolve_relations = resolve_relations;
+    }
+
+    pub fn resolve_relations(&self) -> bool {
+        self.resolve_relations
+    }
+
+    /// query more than one entry into a collection with relation graph.
+    ///
+    /// with relations resolved,linked entries are queried one level deep as a second
+    /// batch with the same retry policy,concurrency and cancellation,and only what is
+    /// left of the deadline.
+    ///
+    /// checkpoint,report file and progress only see entries asked for,failures of
+    /// linked ones are kept in [`HanDianCYCollection::linked_report`].
+    pub async fn query_collection(&self, entries: &[String]) -> Result<HanDianCYCollection> {
+        let started = Instant::now();
+        let (items, report) = self.query_batch_report(entries).await?.into_parts();
+        let chengyucol = items
+            .into_iter()
+            .filter_map(BatchItem::into_entry)
+            .collect::<Vec<_>>();
+        if !self.resolve_relations {
+            return Ok(HanDianCYCollection {
+                report,
+                ..HanDianCYCollection::new(chengyucol, vec![])
+            });
+        }
+        let known = chengyucol
+            .iter()
+            .map(|cy| cy.entry.clone())
+            .collect::<HashSet<_>>();
+        let mut linked = chengyucol
+            .iter()
+            .flat_map(|cy| cy.synonyms.iter().chain(&cy.antonyms))
+            .filter(|w| !known.contains(*w))
+            .cloned()
+            .collect::<Vec<_>>();
+        linked.sort();
+        linked.dedup();
+        let mut options = self.batch_options().clone();
+        options.set_report(None::<PathBuf>);
+        options.set_checkpoint(None::<PathBuf>);
+        options.set_progress(None);
+        if let Some(deadline) = options.deadline() {
+            options.set_deadline(Some(deadline.saturating_sub(started.elapsed())));
+        }
+        let (linked, linked_report) = batch::run(
+            &options,
+            &linked,
+            |w| self.config().entry_url(&w.replace("，", "")),
+            |entry| async move { self.query_one(&entry).await },
+        )
+        .await?
+        .into_parts();
+        let linked = linked
+            .into_iter()
+            .filter_map(BatchItem::into_entry)
+            .collect();
+        Ok(HanDianCYCollection {
+            report,
+            linked_report,
+            ..HanDianCYCollection::new(chengyucol, linked)
+        })
+    }
+}
+
+deref_site!(HanDian);
+
+impl<F: Fetcher> Dictionary for HanDian<F> {
+    type Entry = HanDianCY;
+    const SOURCE: &'static str = "zdic.net";
+
+    async fn query_one(&self, word: &str) -> Result<HanDianCY> {
+        query_entry(self.fetcher(), self.config(), word.to_string()).await
+    }
+
+    async fn query_batch_report(&self, words: &[String]) -> Result<BatchOutput<HanDianCY>> {
+        batch::run(
+            self.batch_options(),
+            words,
+            |w| self.config().entry_url(&w.replace("，", "")),
+            |entry| async move { self.query_one(&entry).await },
+        )
+        .await
+    }
+
+    fn query_stream<'a>(
+        &'a self,
+        words: &[String],
+    ) -> impl Stream<Item = Result<HanDianCY>> + Send + 'a {
+        batch::stream(self.batch_options(), words, move |entry| async move {
+            self.query_one(&entry).await
+        })
+    }
+}
+/// [`Dictionary`] of ordinary words (词语) from `https://www.zdic.net/`
+#[derive(Debug, Clone)]
+pub struct HanDianCi<F = HttpFetcher> {
+    site: Site<F>,
+}
+
+impl Default for HanDianCi {
+    fn default() -> Self {
+        Self {
+            site: Site::new(SourceConfig::new(PREFIX_URL)),
+        }
+    }
+}
+
+impl HanDianCi {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn with_config(config: SourceConfig) -> Self {
+        Self {
+            site: Site::new(config),
+        }
+    }
+}
+
+impl<F: Fetcher> HanDianCi<F> {
+    /// see [`Site::with_fetcher`]
+    pub fn with_fetcher(config: SourceConfig, fetcher: F) -> Self {
+        Self {
+            site: Site::with_fetcher(config, fetcher),
+        }
+    }
+}
+
+deref_site!(HanDianCi);
+
+impl<F: Fetcher> Dictionary for HanDianCi<F> {
+    type Entry = HanDianWord;
+    const SOURCE: &'static str = "zdic.net";
+
+    async fn query_one(&self, word: &str) -> Result<HanDianWord> {
+        query_word_entry(self.fetcher(), self.config(), word).await
+    }
+
+    async fn query_batch_report(&self, words: &[String]) -> Result<BatchOutput<HanDianWord>> {
+        batch::run(
+            self.batch_options(),
+            words,
+            |w| self.config().entry_url(w),
+            |entry| async move { self.query_one(&entry).await },
+        )
+        .await
+    }
+
+    fn query_stream<'a>(
+        &'a self,
+        words: &[String],
+    ) -> impl Stream<Item = Result<HanDianWord>> + Send + 'a {
+        batch::stream(self.batch_options(), words, move |entry| async move {
+            self.query_one(&entry).await
+        })
+    }
 }
 /// query only one entry
 ///
 /// # example
-/// ```
+/// ```ignore
 ///     use tokio::runtime::Runtime;
 /// let rt=Runtime::new();
 /// let r=rt.unwrap().block_on(query_one("火中取栗")).unwrap();
@@ -162,16 +803,10 @@ impl HanDianCYCollection {
 ///
 /// {"pinyin":"huǒ zhōng qǔ lì","entry":"火中取栗","source":"十七世纪法国寓言诗人拉·封丹的寓言《猴子与猫》载：猴子骗猫取火中栗子，栗子让猴子吃了，猫却把脚上的毛烧掉了。","meaning":"偷取炉中烤熟的栗子。比喻受人利用，冒险出力却一
 /// 无所得。","example":"我们目前自顾不暇，郑成功不来就是天主保佑了，我们还好去惹他么。我们不能为别人～。 ◎郭沫若《
-/// 郑成功》第五章","synonym":"代人受过、为人作嫁","antonym":"坐享其成"}
+/// 郑成功》第五章","synonyms":["代人受过","为人作嫁"],"antonyms":["坐享其成"]}
 /// ```
 pub async fn query_one(entry: &str) -> Result<String> {
-    let url = format!("{}{}", PREFIX_URL, entry);
-    let mut cy = HanDianCY::new(entry.into());
-    let html = request_text(&url).await?;
-    let py = parse_pinyin(&html, PINYIN)?;
-    cy.set_pinyin(py);
-    let def_block = parse_definttion_block(&html, DEFINITIONS)?;
-    cy.set_definitions(def_block);
+    let cy = HanDian::new().query_one(entry).await?;
     let json = serde_json::to_string(&cy)?;
     Ok(json)
 }
@@ -195,121 +830,168 @@ pub async fn query_batch_json(entries: &[String]) -> Result<String> {
 ///
 /// return [`HanDianCYCollection`]
 ///# example
-/// ```  
+/// ```ignore
 /// use tokio::runtime::Runtime;
 /// let rt=Runtime::new();
 /// let r=rt.unwrap().block_on(query_batch(&["火中取栗".to_string(),"无法无天".to_string()])).unwrap();
 /// ```
 pub async fn query_batch(entries: &[String]) -> Result<HanDianCYCollection> {
-    // use for loop instead of iter map
-    let urls = entries
-        .iter()
-        .map(|e| -> Result<(String, String)> {
-            Ok((e.to_string(), to_url_code(format!("{}{}", PREFIX_URL, e))?))
-        })
-        .collect::<Vec<_>>();
-    let mut v = vec![];
-    let cys = Arc::new(Mutex::new(vec![]));
-    let file = Arc::new(Mutex::new(
-        fs::OpenOptions::new()
-            .create(true)
-            .append(true)
-            .open("error.txt")?,
-    ));
-    for u in urls {
-        v.push(u?);
+    HanDian::new().query_collection(entries).await
+}
+/// fetch the page of one entry and parse it into [`HanDianCY`]
+async fn query_entry(
+    fetcher: &impl Fetcher,
+    config: &SourceConfig,
+    entry: String,
+) -> Result<HanDianCY> {
+    let url = config.entry_url(&entry.replace("，", ""))?;
+    let html = fetcher.text(&url, config).await;
+    html.and_then(|html| parse_entry(&entry, &html))
+        .map_err(|e| e.with_context(<HanDian as Dictionary>::SOURCE, &entry, &url))
+}
+/// parse page of `entry`.
+///
+/// zdic.net answers an unknown entry with a search page instead of 404,so a page with
+/// neither pinyin nor definitions is [`Error::NotFound`].
+fn parse_entry(entry: &str, html: &str) -> Result<HanDianCY> {
+    let mut cy = HanDianCY::new(entry.into());
+    match (
+        parse_pinyin(html, PINYIN),
+        parse_definttion_block(html, DEFINITIONS),
+    ) {
+        (Err(Error::MissingField { .. }), Err(Error::MissingField { .. })) => {
+            Err(Error::NotFound(Context::default()))
+        }
+        (py, def_block) => {
+            cy.set_readings(py?);
+            cy.set_definitions(def_block?);
+            Ok(cy)
+        }
     }
-    let group = group_by_range(v, 15);
-
-    let (tx, mut rx) = tokio::sync::mpsc::channel(15);
-    tokio::spawn(async move {
-        for url_group in group {
-            if tx.send(url_group).await.is_err() {
-                println!("receiver dropped");
-                return;
+}
+/// fetch the page of one ordinary word and parse it into [`HanDianWord`]
+async fn query_word_entry(
+    fetcher: &impl Fetcher,
+    config: &SourceConfig,
+    entry: &str,
+) -> Result<HanDianWord> {
+    let url = config.entry_url(entry)?;
+    let html = fetcher.text(&url, config).await;
+    html.and_then(|html| parse_word_entry(entry, &html))
+        .map_err(|e| e.with_context(<HanDianCi as Dictionary>::SOURCE, entry, &url))
+}
+/// parse page of ordinary word `entry`,a page with neither pinyin nor senses is
+/// [`Error::NotFound`].
+fn parse_word_entry(entry: &str, html: &str) -> Result<HanDianWord> {
+    let mut word = HanDianWord::new(entry.into());
+    let pinyin = parse_pinyin(html, PINYIN);
+    let document = Html::parse_document(html);
+    let first = pinyin.as_ref().ok().and_then(|r| r.first()).cloned();
+    if let Some(definitions) = document.select(&parse_selector(WORD_DEFINITIONS)?).next() {
+        word.senses = parse_senses(definitions, first.unwrap_or_default());
+    }
+    let readings = match pinyin {
+        Err(Error::MissingField { .. }) if word.senses.is_empty() => {
+            return Err(Error::NotFound(Context::default()))
+        }
+        pinyin => pinyin?,
+    };
+    word.readings = readings.iter().map(|r| Reading::parse(r)).collect();
+    word.pinyin = readings.into_iter().next().unwrap_or_default();
+    if word.senses.is_empty() {
+        return Err(Error::missing_field("senses"));
+    }
+    word.synonyms = texts(&document, WORD_SYNONYMS)?;
+    word.antonyms = texts(&document, WORD_ANTONYMS)?;
+    Ok(word)
+}
+/// senses of every `ol` child of definitions,numbered from 1 in each and put under
+/// the reading before it,an `ol` nested in a sense is not a sense of its own.
+///
+/// an entry with one reading may have no `span.dicpy` in definitions,its senses belong
+/// to `pinyin`,the first reading of the page.
+fn parse_senses(definitions: ElementRef, mut pinyin: String) -> Vec<Sense> {
+    let mut senses = vec![];
+    for e in definitions.children().filter_map(ElementRef::wrap) {
+        if e.value().name() != "ol" {
+            // a reading is either the child itself or inside it,e.g. `<p><span>`
+            let reading = e.descendants().filter_map(ElementRef::wrap);
+            if let Some(py) = reading
+                .filter(|d| d.value().classes().any(|c| c == WORD_PINYIN))
+                .last()
+            {
+                pinyin = own_text(py);
+            }
+        } else {
+            let items = e.children().filter_map(ElementRef::wrap);
+            let items = items.filter(|li| li.value().name() == "li");
+            for (i, li) in items.enumerate() {
+                senses.push(parse_sense(&pinyin, i + 1, li));
             }
         }
-    });
-
-    while let Some(ug) = rx.recv().await {
-        let mut handles = vec![];
-        for (entry, url) in ug {
-            let cys = cys.clone();
-            let file = file.clone();
-            let mut limit = 0;
-
-            handles.push(tokio::spawn(async move {
-                loop {
-                    limit += 1;
-                    if limit >= 3 {
-                        break;
-                    }
-                    if let Err(e) = run_task(cys.clone(), entry.clone()).await {
-                        file.lock()
-                            .await
-                            .write(format!("{}\n", entry).as_bytes())
-                            .unwrap();
-                        println!("{}", e);
-                        break;
-                    } else {
-                        break;
-                    }
+    }
+    senses
+}
+/// split a `li` of sense into definition and examples,examples lose their `例：`,and
+/// a nested `ol` is left out of definition.
+fn parse_sense(pinyin: &str, number: usize, li: ElementRef) -> Sense {
+    let mut definition = String::new();
+    let mut examples = vec![];
+    for child in li.children() {
+        match ElementRef::wrap(child) {
+            Some(e) if e.value().classes().any(|c| c == WORD_EXAMPLE) => {
+                let example = text_of(e);
+                examples.push(example.trim_start_matches("例：").trim().to_string());
+            }
+            Some(e) if e.value().name() == "ol" => {}
+            Some(e) => definition.push_str(&e.text().collect::<String>()),
+            None => {
+                if let Some(text) = child.value().as_text() {
+                    definition.push_str(text);
                 }
-            }));
+            }
         }
-        join_all(handles).await;
     }
-
-    let mut temp = vec![];
-    cys.lock()
-        .await
-        .iter()
-        .for_each(|e| temp.push(e.to_owned()));
-    let cyc = HanDianCYCollection::new(temp);
-
-    Ok(cyc)
-}
-async fn run_task(cys: Arc<Mutex<Vec<HanDianCY>>>, entry: String) -> Result<()> {
-    println!("{entry}");
-    let url = to_url_code(format!("{}{}", PREFIX_URL, entry.replace("，", "")))?;
-    let mut cy = HanDianCY::new(entry);
-    let html = request_text(&url).await?;
-    let py = parse_pinyin(&html, PINYIN)?;
-    cy.set_pinyin(py);
-    let def_block = parse_definttion_block(&html, DEFINITIONS)?;
-    cy.set_definitions(def_block);
-    cys.lock().await.push(cy);
-    Ok(())
+    Sense {
+        pinyin: pinyin.into(),
+        number,
+        definition: definition.trim().to_string(),
+        examples,
+    }
 }
 ///
-/// return pinyin str.
+/// return text of every reading,an entry of 多音字 may have several.
 ///
 /// # example
-/// ```
+/// ```ignore
 ///  let html=r#"<span class="dicpy">yù gài mí zhāng</span>  "#;
 /// let py=parse_pinyin(html, PINYIN).unwrap();
 /// ```
 ///
 /// # Errors
 ///
-/// This function will return an error if pinyin element not found.
-fn parse_pinyin(html: &str, selector: &str) -> Result<String> {
+/// This function will return an error if no pinyin element has text.
+fn parse_pinyin(html: &str, selector: &str) -> Result<Vec<String>> {
     let (document, selector) = selector_parse_doc(html, selector)?;
-    let mut elements = document.select(&selector);
-    // assume there is only one ele
-    let py = if let Some(e) = elements.next() {
-        let mut py = e
-            .text()
-            .into_iter()
-            .map(<str as ToString>::to_string)
-            .collect::<Vec<_>>();
-        py.remove(0)
-    } else {
-        return Err(crate::Error::ParseHtmlSelector(
-            "pinyin item not found".into(),
-        ));
-    };
-    Ok(py)
+    let mut readings = vec![];
+    for e in document.select(&selector) {
+        let py = own_text(e);
+        if !py.is_empty() && !readings.contains(&py) {
+            readings.push(py);
+        }
+    }
+    if readings.is_empty() {
+        return Err(Error::missing_field("pinyin"));
+    }
+    Ok(readings)
+}
+/// own text of pinyin element,children are buttons like audio play
+fn own_text(e: ElementRef) -> String {
+    e.children()
+        .filter_map(|c| c.value().as_text().map(|t| t.to_string()))
+        .collect::<String>()
+        .trim()
+        .to_string()
 }
 
 /// parse html to get a collections of p elements which contain all sorts of definitions.
@@ -318,7 +1000,7 @@ fn parse_pinyin(html: &str, selector: &str) -> Result<String> {
 /// return a string contains all sorts of fields of [`HanDianCY`]
 ///
 /// # return sample
-/// ```
+/// ```text
 /// 【解释】盖：遮掩；弥：更加；彰：明显。想掩盖坏事的真相，结果反而更明显地暴露出来。
 /// 【出处】《左传·昭公三十一年》：“或求名而不得，或欲盖而名章，惩不义也。”
 /// 【示例】与其～，倒不如自己先认了。 ◎闻一多《画展》
@@ -328,7 +1010,7 @@ fn parse_pinyin(html: &str, selector: &str) -> Result<String> {
 ///
 /// # Errors
 ///
-/// This function will return an error if .
+/// This function will return an error if definition block not found.
 fn parse_definttion_block(html: &str, selector: &str) -> Result<Vec<String>> {
     let (document, selector) = selector_parse_doc(html, selector)?;
     let mut element = document.select(&selector);
@@ -337,18 +1019,11 @@ fn parse_definttion_block(html: &str, selector: &str) -> Result<Vec<String>> {
         let (html, sel) = selector_parse_frac(&e.html(), "p")?;
         let elements = html.select(&sel);
         for ele in elements {
-            let item = ele
-                .text()
-                .into_iter()
-                .map(<str as ToString>::to_string)
-                .collect::<Vec<_>>()
-                .remove(0);
-            block.push(item);
+            // text of links and emphasis inside a line belongs to it too
+            block.push(ele.text().collect::<String>());
         }
     } else {
-        return Err(crate::Error::ParseHtmlSelector(
-            "div element not found".into(),
-        ));
+        return Err(Error::missing_field("definitions"));
     }
 
     Ok(block)
@@ -359,11 +1034,12 @@ fn parse_definttion_block(html: &str, selector: &str) -> Result<Vec<String>> {
 fn test_get_page() {
     use tokio::runtime::Runtime;
     // use std::io::Write;
-    use crate::utils::request_text;
 
     let rt = Runtime::new().unwrap();
     let link = "https://www.zdic.net/hans/%E6%AC%B2%E7%9B%96%E5%BC%A5%E5%BD%B0";
-    let s = rt.block_on(request_text(link)).unwrap();
+    let s = rt
+        .block_on(crate::fixture::test_fetcher().text(link, &SourceConfig::new(PREFIX_URL)))
+        .unwrap();
     assert!(s.contains("content definitions cnr"));
 
     // let  f=std::fs::File::create("./t.html");
@@ -374,13 +1050,253 @@ fn test_parse_pinyin() {
     let html = r#"<span class="dicpy">yù gài mí zhāng</span>  "#;
     let py = parse_pinyin(html, PINYIN).unwrap();
 
-    assert_eq!("yù gài mí zhāng".to_string(), py)
+    assert_eq!(vec!["yù gài mí zhāng".to_string()], py);
+
+    let html = r#"<span class="dicpy">zhōng yōng zhī dào<a class="audio"></a></span>
+<span class="dicpy">zhòng yōng zhī dào</span><span class="dicpy"></span>"#;
+    let py = parse_pinyin(html, PINYIN).unwrap();
+    assert_eq!(py, ["zhōng yōng zhī dào", "zhòng yōng zhī dào"]);
+    assert!(matches!(
+        parse_pinyin(r#"<span class="dicpy"> </span>"#, PINYIN),
+        Err(Error::MissingField { .. })
+    ));
 }
 
 #[test]
 fn test_query_one() {
     use tokio::runtime::Runtime;
-    let rt = Runtime::new();
-    let r = rt.unwrap().block_on(query_one("总而言之")).unwrap();
-    println!("{}", r)
+    let mut handian = HanDian::new();
+    handian.set_fetcher(crate::fixture::test_fetcher());
+    let rt = Runtime::new().unwrap();
+    let r = rt.block_on(handian.query_one("总而言之")).unwrap();
+    assert_eq!(r.pinyin(), "zǒng ér yán zhī");
+    assert_eq!(r.readings()[0].numbered(), "zong3 er2 yan2 zhi1");
+    assert_eq!(r.readings()[0].initials(), "zeyz");
+    assert_eq!(r.grammar().unwrap(), "复句式；作分句；用于总结");
+
+    let r = rt.block_on(handian.query_one("欲盖弥彰")).unwrap();
+    let example = r.example_citation().unwrap();
+    assert_eq!(example.quote().unwrap(), "与其欲盖弥彰，倒不如自己先认了。");
+    assert_eq!(
+        (
+            example.author().unwrap().as_str(),
+            example.work().unwrap().as_str()
+        ),
+        ("闻一多", "画展")
+    );
+    assert_eq!(r.source_citation().unwrap().work().unwrap(), "左传");
+}
+
+#[test]
+fn test_parse_entry_not_found() {
+    let search_page = r#"<div class="nr-box">没有找到</div>"#;
+    assert!(matches!(
+        parse_entry("无此成语", search_page),
+        Err(Error::NotFound(_))
+    ));
+    let no_definitions = r#"<span class="dicpy">yù gài mí zhāng</span>"#;
+    assert!(matches!(
+        parse_entry("欲盖弥彰", no_definitions),
+        Err(Error::MissingField { field, .. }) if field == "definitions"
+    ));
+}
+
+#[test]
+fn test_set_definitions() {
+    let mut cy = HanDianCY::new("欲盖弥彰".into());
+    cy.set_definitions(
+        [
+            "【解释】想掩盖坏事的真相，结果反而更明显地暴露出来。",
+            "【语法】紧缩式；作谓语、宾语、定语；含贬义",
+            "【歇后语】猫屎上盖沙",
+            "【英文】the more one tries to hide,the more one is exposed",
+            "【故事】春秋时期，",
+            "邾国大夫黑肱以滥地投奔鲁国。",
+            "【成语举例】无",
+        ]
+        .map(String::from)
+        .to_vec(),
+    );
+    assert_eq!(cy.grammar().unwrap(), "紧缩式；作谓语、宾语、定语；含贬义");
+    assert_eq!(cy.xiehouyu().unwrap(), "猫屎上盖沙");
+    assert!(cy.english().unwrap().starts_with("the more"));
+    assert_eq!(
+        cy.story().unwrap(),
+        "春秋时期，\n邾国大夫黑肱以滥地投奔鲁国。"
+    );
+    assert_eq!(cy.others().get("成语举例").unwrap(), "无");
+    assert!(cy.source().is_none() && cy.source_citation().is_none());
+}
+
+#[test]
+fn test_query_word() {
+    use tokio::runtime::Runtime;
+    let mut handian = HanDianCi::new();
+    handian.set_fetcher(crate::fixture::test_fetcher());
+    let rt = Runtime::new().unwrap();
+    let word = rt.block_on(handian.query_one("朋友")).unwrap();
+    assert_eq!(word.pinyin(), "péng you");
+    assert_eq!(word.senses().len(), 2);
+    assert_eq!(word.senses()[0].definition(), "彼此有交情的人。");
+    assert_eq!(word.senses()[0].examples(), ["他是我的老～。"]);
+    assert_eq!(
+        (word.senses()[1].number(), word.senses()[1].examples().len()),
+        (2, 0)
+    );
+    assert_eq!(word.senses()[1].pinyin(), "péng you");
+    assert_eq!(word.synonyms(), ["友人", "伙伴"]);
+    assert_eq!(word.antonyms(), ["敌人"]);
+
+    // senses are numbered under each reading
+    let html = r#"<span class="dicpy">dì dào</span>
+<div class="content definitions jnr">
+ <p><span class="dicpy">dì dào</span></p>
+ <ol><li><p>地下通道。</p><ol><li>战时的地道。</li></ol></li><li><p>纯粹。</p></li></ol>
+ <p><span class="dicpy">dì dao</span></p>
+ <ol><li><p>真正是有名产地出产的。</p></li></ol>
+</div>"#;
+    let word = parse_word_entry("地道", html).unwrap();
+    let senses = word
+        .senses()
+        .iter()
+        .map(|s| (s.pinyin(), s.number()))
+        .collect::<Vec<_>>();
+    assert_eq!(senses, [("dì dào", 1), ("dì dào", 2), ("dì dao", 1)]);
+    assert_eq!(word.senses()[0].definition(), "地下通道。");
+    assert!(matches!(
+        parse_word_entry("朋友", "<html></html>"),
+        Err(Error::NotFound(_))
+    ));
+}
+
+#[test]
+fn test_citation() {
+    let source = Citation::parse_source(
+        "《左传·昭公三十一年》：“或求名而不得，或欲盖而名章，惩不义也。”",
+        "欲盖弥彰",
+    );
+    assert_eq!(source.work().unwrap(), "左传");
+    assert_eq!(source.chapter().unwrap(), "昭公三十一年");
+    assert_eq!(
+        source.quote().unwrap(),
+        "或求名而不得，或欲盖而名章，惩不义也。"
+    );
+    assert!(source.author().is_none());
+    for text in ["语出《论语·学而》：“学而时习之。”", "见《论语·学而》"]
+    {
+        let source = Citation::parse_source(text, "x");
+        assert_eq!(source.work().unwrap(), "论语");
+        assert_eq!(source.chapter().unwrap(), "学而");
+        assert!(source.author().is_none(), "{}", text);
+    }
+    let source = Citation::parse_source("语出宋·朱熹《朱子语类》", "x");
+    assert_eq!(
+        (
+            source.dynasty().unwrap().as_str(),
+            source.author().unwrap().as_str()
+        ),
+        ("宋", "朱熹")
+    );
+
+    let source = Citation::parse_source("南朝·宋·刘义庆《世说新语·言语》：“～。”", "x");
+    assert_eq!(
+        (
+            source.dynasty().unwrap().as_str(),
+            source.author().unwrap().as_str()
+        ),
+        ("南朝·宋", "刘义庆")
+    );
+    let source = Citation::parse_source(
+        "十七世纪法国寓言诗人拉·封丹的寓言《猴子与猫》载：猴子骗猫取火中栗子。",
+        "火中取栗",
+    );
+    assert_eq!(source.work().unwrap(), "猴子与猫");
+    assert!(source.author().is_none() && source.chapter().is_none());
+    assert_eq!(source.quote().unwrap(), "猴子骗猫取火中栗子。");
+
+    let example = Citation::parse_example("我们不能为别人～。 ◎郭沫若《郑成功》第五章", "火中取栗");
+    assert_eq!(example.quote().unwrap(), "我们不能为别人火中取栗。");
+    assert_eq!(example.author().unwrap(), "郭沫若");
+    assert_eq!(example.work().unwrap(), "郑成功");
+    assert_eq!(example.chapter().unwrap(), "第五章");
+}
+
+#[test]
+fn test_query_collection() {
+    use crate::fetcher::{MemoryFetcher, Response};
+    use tokio::runtime::Runtime;
+    let page = |py: &str, lines: &str| {
+        Response::ok(format!(
+            r#"<span class="dicpy">{}</span><div class="content definitions cnr">{}</div>"#,
+            py, lines
+        ))
+    };
+    let config = SourceConfig::new("http://127.0.0.1/hans/");
+    let mut fetcher = MemoryFetcher::new();
+    fetcher.insert(
+        config.entry_url("火中取栗").unwrap(),
+        page(
+            "huǒ zhōng qǔ lì",
+            "<p>【近义词】代人受过、为人作嫁</p><p>【反义词】坐享其成</p>",
+        ),
+    );
+    fetcher.insert(
+        config.entry_url("坐享其成").unwrap(),
+        page("zuò xiǎng qí chéng", "<p>【反义词】火中取栗</p>"),
+    );
+    let mut handian = HanDian::with_fetcher(config, fetcher);
+    let rt = Runtime::new().unwrap();
+    let words = ["火中取栗".to_string()];
+
+    let col = rt.block_on(handian.query_collection(&words)).unwrap();
+    let cy = col.get("火中取栗").unwrap();
+    assert_eq!(cy.synonyms(), ["代人受过", "为人作嫁"]);
+    assert_eq!(cy.antonyms(), ["坐享其成"]);
+    assert_eq!(col.relations().len(), 3);
+    assert!(col.relations().iter().all(|r| !r.is_resolved()));
+
+    handian.set_resolve_relations(true);
+    let checkpoint = std::env::temp_dir().join(format!("crawlins-col-{}", std::process::id()));
+    handian.set_checkpoint(Some(&checkpoint));
+    let (tx, mut rx) = tokio::sync::mpsc::unbounded_channel();
+    handian.set_progress(Some(crate::ProgressHandler::from_sender(tx)));
+    let col = rt.block_on(handian.query_collection(&words)).unwrap();
+    handian.set_progress(None);
+    let mut started = 0;
+    while let Some(e) = rx.blocking_recv() {
+        if let crate::ProgressEvent::Started { word } = e {
+            assert_eq!(word, "火中取栗");
+            started += 1;
+        }
+    }
+    assert_eq!(started, 1);
+    let content = std::fs::read_to_string(&checkpoint).unwrap();
+    assert_eq!(content.lines().count(), 1);
+    std::fs::remove_file(&checkpoint).unwrap();
+    handian.set_checkpoint(None::<PathBuf>);
+    assert_eq!(col.chengyucol().len(), 1);
+    assert_eq!(col.linked()[0].entry(), "坐享其成");
+    // pages of the other two linked entries are missing
+    assert!(col.report().is_empty());
+    assert_eq!(col.linked_report().words(), ["为人作嫁", "代人受过"]);
+    let resolved = col
+        .relations()
+        .iter()
+        .filter(|r| r.is_resolved())
+        .map(|r| (r.from(), r.to(), r.kind()))
+        .collect::<Vec<_>>();
+    assert_eq!(
+        resolved,
+        [
+            ("火中取栗", "坐享其成", RelationKind::Antonym),
+            ("坐享其成", "火中取栗", RelationKind::Antonym)
+        ]
+    );
+    // a cancelled batch queries nothing,linked entries included
+    let token = tokio_util::sync::CancellationToken::new();
+    token.cancel();
+    handian.set_cancellation(Some(token));
+    let col = rt.block_on(handian.query_collection(&words)).unwrap();
+    assert!(col.chengyucol().is_empty() && col.linked().is_empty());
+    assert_eq!(col.report().failures()[0].kind(), "cancelled");
 }
@@ -0,0 +1,402 @@
+//! single characters (汉字) from website `https://www.zdic.net/ (汉典网)`
+//!
+//! a character has a page under the same uri as chengyu,e.g.
+//! `https://www.zdic.net/hans/%E5%A5%BD （好转码）`,only its layout differs.
+//!
+//! # Analysis of character web page.
+//! ## 读音部分：
+//! ```text
+//! <span class="z_d song">hǎo</span> <span class="z_d song">hào</span>
+//! ```
+//!
+//! ## 字形和编码部分，每项是一个标签加上值：
+//! ```text
+//! <p><span class="z_ts2">部首</span> <a href="/zd/bs/?bs=女">女</a></p>
+//! <p><span class="z_ts2">部外</span> 3</p>
+//! <p><span class="z_ts2">总笔画</span> 6</p>
+//! <p><span class="z_ts2">结构</span> 左右结构</p>
+//! <p><span class="z_ts2">统一码</span> U+597D</p>
+//! <p><span class="z_ts2">五笔</span> VBG</p>
+//! <p><span class="z_ts2">仓颉</span> VND</p>
+//! ```
+//!
+//! ## 基本解释和详细解释，每个读音后面是它的义项：
+//! ```text
+//! <div class="content definitions jnr">
+//!  <p class="dicpy">hǎo</p><ol><li>优点多的；使人满意的。与“坏”相对：～人。</li></ol>
+//!  <p class="dicpy">hào</p><ol><li>喜爱（跟“恶”相对）：～学。</li></ol>
+//! </div>
+//! <div class="content definitions xnr"> ... </div>
+//! ```
+//!
+//! ## 相关词语：
+//! ```text
+//! <div class="content xgcy"><a href="/hans/好人">好人</a> <a href="/hans/好学">好学</a></div>
+//! ```
+use crate::{
+    batch::{self, BatchOutput},
+    config::SourceConfig,
+    dictionary::Dictionary,
+    error::{Context, Error, Result},
+    fetch::HttpFetcher,
+    fetcher::Fetcher,
+    site::{deref_site, Site},
+    utils::{parse_selector, text_of, texts},
+};
+use futures::Stream;
+use scraper::{ElementRef, Html};
+use serde::{Deserialize, Serialize};
+
+// selectors follow the layout above,which is checked only against the hand-written
+// fixture of 好 until a recorded page replaces it,see `fixtures/README.md`
+static READING: &str = "span.z_d";
+static LABEL: &str = "span.z_ts2";
+static BASIC: &str = "div.definitions.jnr";
+static DETAILED: &str = "div.definitions.xnr";
+static SENSE: &str = "p.dicpy, li";
+static RELATED: &str = "div.xgcy a";
+static PREFIX_URL: &str = "https://www.zdic.net/hans/";
+
+/// meanings of a character under one reading
+#[derive(Debug, Default, Serialize, Deserialize, Clone, PartialEq, Eq)]
+pub struct Meanings {
+    pinyin: String,
+    meanings: Vec<String>,
+}
+
+impl Meanings {
+    fn new(pinyin: String) -> Self {
+        Self {
+            pinyin,
+            meanings: vec![],
+        }
+    }
+
+    pub fn pinyin(&self) -> &str {
+        self.pinyin.as_ref()
+    }
+
+    pub fn meanings(&self) -> &[String] {
+        self.meanings.as_ref()
+    }
+}
+
+/// It means a single character of handian.
+#[derive(Debug, Default, Serialize, Deserialize, Clone)]
+pub struct HanZi {
+    character: String,
+    /// 拼音 of every reading
+    readings: Vec<String>,
+    /// 部首
+    radical: Option<String>,
+    /// 部外笔画
+    radical_strokes: Option<u32>,
+    /// 总笔画
+    strokes: Option<u32>,
+    /// 结构,e.g. 左右结构
+    structure: Option<String>,
+    /// 统一码,e.g. `U+597D`
+    unicode: String,
+    /// 五笔
+    wubi: Option<String>,
+    /// 仓颉
+    cangjie: Option<String>,
+    /// 基本解释
+    basic: Vec<Meanings>,
+    /// 详细解释
+    detailed: Vec<Meanings>,
+    /// 相关词语
+    related: Vec<String>,
+}
+
+impl HanZi {
+    fn new(character: String) -> Self {
+        let unicode = character
+            .chars()
+            .next()
+            .map(|c| format!("U+{:04X}", c as u32))
+            .unwrap_or_default();
+        Self {
+            character,
+            unicode,
+            ..Default::default()
+        }
+    }
+
+    /// set field of a `label` of glyph and codes part
+    fn set_label(&mut self, label: &str, value: String) {
+        match label {
+            "部首" => self.radical = Some(value),
+            "部外" => self.radical_strokes = value.parse().ok(),
+            "总笔画" => self.strokes = value.parse().ok(),
+            "结构" => self.structure = Some(value),
+            "统一码" => {
+                // e.g. 基本区 U+597D
+                if let Some(code) = value.split_whitespace().find(|v| v.starts_with("U+")) {
+                    self.unicode = code.into();
+                }
+            }
+            "五笔" => self.wubi = Some(value),
+            "仓颉" => self.cangjie = Some(value),
+            _ => {}
+        }
+    }
+
+    pub fn character(&self) -> &str {
+        self.character.as_ref()
+    }
+
+    pub fn readings(&self) -> &[String] {
+        self.readings.as_ref()
+    }
+
+    pub fn radical(&self) -> Option<&String> {
+        self.radical.as_ref()
+    }
+
+    pub fn radical_strokes(&self) -> Option<u32> {
+        self.radical_strokes
+    }
+
+    pub fn strokes(&self) -> Option<u32> {
+        self.strokes
+    }
+
+    pub fn structure(&self) -> Option<&String> {
+        self.structure.as_ref()
+    }
+
+    pub fn unicode(&self) -> &str {
+        self.unicode.as_ref()
+    }
+
+    pub fn wubi(&self) -> Option<&String> {
+        self.wubi.as_ref()
+    }
+
+    pub fn cangjie(&self) -> Option<&String> {
+        self.cangjie.as_ref()
+    }
+
+    pub fn basic(&self) -> &[Meanings] {
+        self.basic.as_ref()
+    }
+
+    pub fn detailed(&self) -> &[Meanings] {
+        self.detailed.as_ref()
+    }
+
+    pub fn related(&self) -> &[String] {
+        self.related.as_ref()
+    }
+}
+#[derive(Debug, Default, Serialize, Deserialize)]
+pub struct HanZiCollection {
+    hanzicol: Vec<HanZi>,
+}
+
+impl HanZiCollection {
+    fn new(hanzicol: Vec<HanZi>) -> Self {
+        Self { hanzicol }
+    }
+
+    pub fn hanzicol(&self) -> &[HanZi] {
+        self.hanzicol.as_ref()
+    }
+}
+/// [`Dictionary`] of single characters from `https://www.zdic.net/`
+#[derive(Debug, Clone)]
+pub struct HanDianZi<F = HttpFetcher> {
+    site: Site<F>,
+}
+
+impl Default for HanDianZi {
+    fn default() -> Self {
+        Self {
+            site: Site::new(SourceConfig::new(PREFIX_URL)),
+        }
+    }
+}
+
+impl HanDianZi {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn with_config(config: SourceConfig) -> Self {
+        Self {
+            site: Site::new(config),
+        }
+    }
+}
+
+impl<F: Fetcher> HanDianZi<F> {
+    /// see [`Site::with_fetcher`]
+    pub fn with_fetcher(config: SourceConfig, fetcher: F) -> Self {
+        Self {
+            site: Site::with_fetcher(config, fetcher),
+        }
+    }
+}
+
+deref_site!(HanDianZi);
+
+impl<F: Fetcher> Dictionary for HanDianZi<F> {
+    type Entry = HanZi;
+    const SOURCE: &'static str = "zdic.net";
+
+    async fn query_one(&self, word: &str) -> Result<HanZi> {
+        query_entry(self.fetcher(), self.config(), word).await
+    }
+
+    async fn query_batch_report(&self, words: &[String]) -> Result<BatchOutput<HanZi>> {
+        batch::run(
+            self.batch_options(),
+            words,
+            |w| self.config().entry_url(w),
+            |character| async move { self.query_one(&character).await },
+        )
+        .await
+    }
+
+    fn query_stream<'a>(
+        &'a self,
+        words: &[String],
+    ) -> impl Stream<Item = Result<HanZi>> + Send + 'a {
+        batch::stream(self.batch_options(), words, move |character| async move {
+            self.query_one(&character).await
+        })
+    }
+}
+/// query only one character
+///
+/// return value in json string
+pub async fn query_one(character: &str) -> Result<String> {
+    let zi = HanDianZi::new().query_one(character).await?;
+    let json = serde_json::to_string(&zi)?;
+    Ok(json)
+}
+/// query more than one character
+/// dump json string to file
+pub async fn query_batch_and_dump(characters: &[String], fpath: &str) -> Result<()> {
+    let json = query_batch_json(characters).await?;
+    tokio::fs::write(fpath, json).await?;
+
+    Ok(())
+}
+/// query more than one character
+///
+/// return value in json string
+pub async fn query_batch_json(characters: &[String]) -> Result<String> {
+    let col = query_batch(characters).await?;
+    let json = serde_json::to_string(&col)?;
+    Ok(json)
+}
+/// query more than one character
+///
+/// return [`HanZiCollection`]
+pub async fn query_batch(characters: &[String]) -> Result<HanZiCollection> {
+    let col = HanZiCollection::new(HanDianZi::new().query_batch(characters).await?);
+
+    Ok(col)
+}
+/// fetch the page of one character and parse it into [`HanZi`]
+async fn query_entry(
+    fetcher: &impl Fetcher,
+    config: &SourceConfig,
+    character: &str,
+) -> Result<HanZi> {
+    let url = config.entry_url(character)?;
+    let html = fetcher.text(&url, config).await;
+    html.and_then(|html| parse_entry(character, &html))
+        .map_err(|e| e.with_context(<HanDianZi as Dictionary>::SOURCE, character, &url))
+}
+/// parse page of `character`.
+///
+/// zdic.net answers an unknown character with a search page instead of 404,so a page
+/// with neither readings nor meanings is [`Error::NotFound`].
+fn parse_entry(character: &str, html: &str) -> Result<HanZi> {
+    let document = Html::parse_document(html);
+    let mut zi = HanZi::new(character.into());
+    zi.readings = texts(&document, READING)?;
+    zi.basic = parse_meanings(&document, BASIC)?;
+    zi.detailed = parse_meanings(&document, DETAILED)?;
+    if zi.readings.is_empty() {
+        return Err(if zi.basic.is_empty() && zi.detailed.is_empty() {
+            Error::NotFound(Context::default())
+        } else {
+            Error::missing_field("readings")
+        });
+    }
+    for label in document.select(&parse_selector(LABEL)?) {
+        let name = text_of(label);
+        let value = label
+            .parent()
+            .and_then(ElementRef::wrap)
+            .map(text_of)
+            .unwrap_or_default();
+        let value = value.trim_start_matches(name.as_str()).trim();
+        zi.set_label(&name, value.into());
+    }
+    zi.related = texts(&document, RELATED)?;
+
+    Ok(zi)
+}
+/// readings of a block of meanings,each followed by its meanings
+fn parse_meanings(document: &Html, block: &str) -> Result<Vec<Meanings>> {
+    let mut ret: Vec<Meanings> = vec![];
+    let sense = parse_selector(SENSE)?;
+    if let Some(block) = document.select(&parse_selector(block)?).next() {
+        for e in block.select(&sense) {
+            let text = text_of(e);
+            if e.value().name() == "p" {
+                ret.push(Meanings::new(text));
+            } else if let Some(last) = ret.last_mut() {
+                last.meanings.push(text);
+            } else {
+                // a character of only one reading may not repeat it here
+                let mut meanings = Meanings::default();
+                meanings.meanings.push(text);
+                ret.push(meanings);
+            }
+        }
+    }
+    Ok(ret)
+}
+#[test]
+fn test_parse_entry() {
+    let html = std::fs::read_to_string(concat!(
+        env!("CARGO_MANIFEST_DIR"),
+        "/fixtures/www.zdic.net/hans%2F%25E5%25A5%25BD.html"
+    ))
+    .unwrap();
+    let zi = parse_entry("好", &html).unwrap();
+    assert_eq!(zi.readings(), ["hǎo", "hào"]);
+    assert_eq!(zi.radical().unwrap(), "女");
+    assert_eq!((zi.radical_strokes(), zi.strokes()), (Some(3), Some(6)));
+    assert_eq!(zi.structure().unwrap(), "左右结构");
+    assert_eq!(zi.unicode(), "U+597D");
+    assert_eq!(zi.wubi().unwrap(), "VBG");
+    assert_eq!(zi.cangjie().unwrap(), "VND");
+    assert_eq!(zi.basic().len(), 2);
+    assert_eq!(zi.basic()[1].pinyin(), "hào");
+    assert_eq!(zi.basic()[1].meanings()[0], "喜爱（跟“恶”相对）：～学。");
+    assert_eq!(zi.detailed()[0].meanings().len(), 2);
+    assert_eq!(zi.related(), ["好人", "好学", "好奇"]);
+
+    assert!(matches!(
+        parse_entry("好", "<html></html>"),
+        Err(Error::NotFound(_))
+    ));
+}
+
+#[test]
+fn test_query_one() {
+    use tokio::runtime::Runtime;
+    let mut handian = HanDianZi::new();
+    handian.set_fetcher(crate::fixture::test_fetcher());
+    let rt = Runtime::new().unwrap();
+    let zi = rt.block_on(handian.query_one("好")).unwrap();
+    assert_eq!(zi.character(), "好");
+    assert_eq!(zi.readings()[0], "hǎo");
+}
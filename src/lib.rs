@@ -1,10 +1,39 @@
 //! this is a collection of many instances of crawl.
 //! use features to enable each of functions.
+mod batch;
+mod cache;
+mod charset;
+mod config;
+mod dictionary;
 mod error;
+mod fetch;
+mod fetcher;
+mod fixture;
 #[cfg(feature = "chengyu")]
 pub mod handian;
+#[cfg(feature = "hanzi")]
+pub mod hanzi;
+mod pinyin;
+mod progress;
+mod ratelimit;
+mod retry;
+mod robots;
+mod site;
 mod utils;
 #[cfg(feature = "vocabulary")]
 pub mod vocabulary;
 pub mod webster;
-pub use error::{Error, Result};
+pub use batch::{BatchItem, BatchOptions, BatchOutput, Failure, FailureReport};
+pub use cache::{CacheMode, ResponseCache};
+pub use config::SourceConfig;
+pub use dictionary::Dictionary;
+pub use error::{Context, Error, Result};
+pub use fetch::{FetchConfig, HttpFetcher, UserAgentRotation};
+pub use fetcher::{Fetcher, MemoryFetcher, Response};
+pub use fixture::{DirFetcher, FixtureMode, Fixtures};
+pub use pinyin::{Reading, Syllable};
+pub use progress::{ProgressEvent, ProgressHandler};
+pub use ratelimit::RateLimit;
+pub use retry::RetryPolicy;
+pub use site::Site;
+pub use tokio_util::sync::CancellationToken;
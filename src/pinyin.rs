@@ -0,0 +1,221 @@
+//! structured pinyin,so entries can be sorted and searched by reading.
+//!
+//! a [`Reading`] like `yù gài mí zhāng` is split into [`Syllable`]s,each known in
+//! tone-marked (`zhāng`),numbered-tone (`zhang1`) and toneless (`zhang`) form.
+use serde::{Deserialize, Serialize};
+use std::cmp::Ordering;
+
+/// tone of a syllable without tone mark,written `5` in numbered form
+pub const NEUTRAL_TONE: u8 = 5;
+
+/// 声母,longest first so that `zh` wins over `z`
+static INITIALS: [&str; 23] = [
+    "zh", "ch", "sh", "b", "p", "m", "f", "d", "t", "n", "l", "g", "k", "h", "j", "q", "x", "r",
+    "z", "c", "s", "y", "w",
+];
+
+/// one syllable of pinyin
+#[derive(Debug, Default, Serialize, Deserialize, Clone, PartialEq, Eq, Hash)]
+pub struct Syllable {
+    /// as written on the page,e.g. `zhāng`
+    marked: String,
+    /// lowercase with tone number,e.g. `zhang1`
+    numbered: String,
+    /// lowercase without tone,e.g. `zhang`
+    toneless: String,
+    /// 1 to 4,or [`NEUTRAL_TONE`]
+    tone: u8,
+    /// 声母,empty for a syllable without one like `ér`
+    initial: String,
+}
+
+impl Syllable {
+    /// parse one tone-marked syllable,`None` if it has no letter,e.g. punctuation
+    pub fn parse(marked: &str) -> Option<Self> {
+        let mut toneless = String::new();
+        let mut tone = NEUTRAL_TONE;
+        for c in marked.chars() {
+            match untone(c) {
+                Some((base, t)) => {
+                    if let Some(base) = base {
+                        toneless.push(base);
+                    }
+                    tone = t;
+                }
+                None if c.is_alphabetic() => toneless.extend(c.to_lowercase()),
+                None => {}
+            }
+        }
+        if toneless.is_empty() {
+            return None;
+        }
+        let initial = INITIALS
+            .iter()
+            .find(|i| toneless.starts_with(**i) && toneless.len() > i.len())
+            .map(|i| i.to_string())
+            .unwrap_or_default();
+        Some(Self {
+            marked: marked.into(),
+            numbered: format!("{}{}", toneless, tone),
+            toneless,
+            tone,
+            initial,
+        })
+    }
+
+    pub fn marked(&self) -> &str {
+        self.marked.as_ref()
+    }
+
+    pub fn numbered(&self) -> &str {
+        self.numbered.as_ref()
+    }
+
+    pub fn toneless(&self) -> &str {
+        self.toneless.as_ref()
+    }
+
+    pub fn tone(&self) -> u8 {
+        self.tone
+    }
+
+    pub fn initial(&self) -> &str {
+        self.initial.as_ref()
+    }
+}
+
+/// syllables order by toneless form,then by tone
+impl Ord for Syllable {
+    fn cmp(&self, other: &Self) -> Ordering {
+        (&self.toneless, self.tone, &self.marked).cmp(&(&other.toneless, other.tone, &other.marked))
+    }
+}
+
+impl PartialOrd for Syllable {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+/// one reading of an entry,a list of syllables
+#[derive(Debug, Default, Serialize, Deserialize, Clone, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct Reading {
+    syllables: Vec<Syllable>,
+}
+
+impl Reading {
+    /// split a tone-marked reading on whitespace and punctuation,e.g. `yī bù zuò，èr bù xiū`
+    pub fn parse(text: &str) -> Self {
+        Self {
+            syllables: text
+                .split(|c: char| !(c.is_alphabetic() || is_combining_tone(c)))
+                .filter_map(Syllable::parse)
+                .collect(),
+        }
+    }
+
+    pub fn syllables(&self) -> &[Syllable] {
+        self.syllables.as_ref()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.syllables.is_empty()
+    }
+
+    /// e.g. `yù gài mí zhāng`
+    pub fn marked(&self) -> String {
+        self.join(Syllable::marked)
+    }
+
+    /// e.g. `yu4 gai4 mi2 zhang1`
+    pub fn numbered(&self) -> String {
+        self.join(Syllable::numbered)
+    }
+
+    /// e.g. `yu gai mi zhang`
+    pub fn toneless(&self) -> String {
+        self.join(Syllable::toneless)
+    }
+
+    /// first letter of every syllable,e.g. `ygmz`,as typed to search an entry quickly
+    pub fn initials(&self) -> String {
+        self.syllables
+            .iter()
+            .filter_map(|s| s.toneless.chars().next())
+            .collect()
+    }
+
+    fn join(&self, form: fn(&Syllable) -> &str) -> String {
+        self.syllables
+            .iter()
+            .map(form)
+            .collect::<Vec<_>>()
+            .join(" ")
+    }
+}
+
+fn is_combining_tone(c: char) -> bool {
+    matches!(c, '\u{304}' | '\u{301}' | '\u{30c}' | '\u{300}')
+}
+
+/// base letter and tone of a tone-marked letter,base is `None` for a combining mark
+fn untone(c: char) -> Option<(Option<char>, u8)> {
+    let (base, tone) = match c {
+        'ā' | 'Ā' => ('a', 1),
+        'á' | 'Á' => ('a', 2),
+        'ǎ' | 'Ǎ' => ('a', 3),
+        'à' | 'À' => ('a', 4),
+        'ē' | 'Ē' => ('e', 1),
+        'é' | 'É' => ('e', 2),
+        'ě' | 'Ě' => ('e', 3),
+        'è' | 'È' => ('e', 4),
+        'ī' | 'Ī' => ('i', 1),
+        'í' | 'Í' => ('i', 2),
+        'ǐ' | 'Ǐ' => ('i', 3),
+        'ì' | 'Ì' => ('i', 4),
+        'ō' | 'Ō' => ('o', 1),
+        'ó' | 'Ó' => ('o', 2),
+        'ǒ' | 'Ǒ' => ('o', 3),
+        'ò' | 'Ò' => ('o', 4),
+        'ū' | 'Ū' => ('u', 1),
+        'ú' | 'Ú' => ('u', 2),
+        'ǔ' | 'Ǔ' => ('u', 3),
+        'ù' | 'Ù' => ('u', 4),
+        'ǖ' | 'Ǖ' => ('ü', 1),
+        'ǘ' | 'Ǘ' => ('ü', 2),
+        'ǚ' | 'Ǚ' => ('ü', 3),
+        'ǜ' | 'Ǜ' => ('ü', 4),
+        'ḿ' => ('m', 2),
+        'ń' => ('n', 2),
+        'ň' => ('n', 3),
+        'ǹ' => ('n', 4),
+        '\u{304}' => return Some((None, 1)),
+        '\u{301}' => return Some((None, 2)),
+        '\u{30c}' => return Some((None, 3)),
+        '\u{300}' => return Some((None, 4)),
+        _ => return None,
+    };
+    Some((Some(base), tone))
+}
+
+#[test]
+fn test_reading() {
+    let reading = Reading::parse("zǒng ér yán zhī");
+    assert_eq!(reading.numbered(), "zong3 er2 yan2 zhi1");
+    assert_eq!(reading.toneless(), "zong er yan zhi");
+    assert_eq!(reading.initials(), "zeyz");
+    let initials = reading
+        .syllables()
+        .iter()
+        .map(Syllable::initial)
+        .collect::<Vec<_>>();
+    assert_eq!(initials, ["z", "", "y", "zh"]);
+
+    let reading = Reading::parse("yī bù zuò，èr bù xiū");
+    assert_eq!(reading.marked(), "yī bù zuò èr bù xiū");
+    assert_eq!(Reading::parse("péng you").numbered(), "peng2 you5");
+    assert_eq!(Reading::parse("lǜ").numbered(), "lü4");
+    assert!(Reading::parse(" ，").is_empty());
+    assert!(Reading::parse("hǎo") < Reading::parse("hào"));
+    assert!(Reading::parse("hào") < Reading::parse("hē"));
+}
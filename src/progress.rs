@@ -0,0 +1,122 @@
+//! report progress of a batch query.
+//!
+//! a [`ProgressHandler`] is called with every [`ProgressEvent`] of a batch,it can be
+//! built from a closure,a channel sender or an indicatif progress bar.
+use indicatif::{ProgressBar, ProgressStyle};
+use std::{fmt, sync::Arc, time::Duration};
+use tokio::sync::mpsc::UnboundedSender;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ProgressEvent {
+    /// batch begins,`resumed` words are already finished in checkpoint file
+    BatchStarted {
+        total: usize,
+        resumed: usize,
+    },
+    /// first attempt of a word is sent
+    Started {
+        word: String,
+    },
+    /// an attempt failed and the word will be queried again after `delay`
+    Retried {
+        word: String,
+        attempt: u32,
+        delay: Duration,
+        error: String,
+    },
+    Succeeded {
+        word: String,
+    },
+    /// the word failed after all attempts
+    Failed {
+        word: String,
+        error: String,
+    },
+    BatchFinished {
+        succeeded: usize,
+        failed: usize,
+    },
+}
+
+/// subscriber of [`ProgressEvent`],clones share the same callback.
+#[derive(Clone)]
+pub struct ProgressHandler {
+    callback: Arc<dyn Fn(&ProgressEvent) + Send + Sync>,
+}
+
+impl fmt::Debug for ProgressHandler {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("ProgressHandler").finish_non_exhaustive()
+    }
+}
+
+impl ProgressHandler {
+    pub fn new<F>(callback: F) -> Self
+    where
+        F: Fn(&ProgressEvent) + Send + Sync + 'static,
+    {
+        Self {
+            callback: Arc::new(callback),
+        }
+    }
+
+    /// send every event to a channel,events are dropped once receiver is closed.
+    pub fn from_sender(sender: UnboundedSender<ProgressEvent>) -> Self {
+        Self::new(move |event| {
+            let _ = sender.send(event.clone());
+        })
+    }
+
+    /// draw progress of batch on `bar` for cli use.
+    ///
+    /// length of bar is set when batch starts,retries and failures are printed above it.
+    pub fn progress_bar(bar: ProgressBar) -> Self {
+        if let Ok(style) = ProgressStyle::with_template(
+            "[{elapsed_precise}] {bar:40.cyan/blue} {pos}/{len} {wide_msg}",
+        ) {
+            bar.set_style(style);
+        }
+        Self::new(move |event| match event {
+            ProgressEvent::BatchStarted { total, resumed } => {
+                bar.set_length((total + resumed) as u64);
+                bar.set_position(*resumed as u64);
+            }
+            ProgressEvent::Started { word } => bar.set_message(word.clone()),
+            ProgressEvent::Retried {
+                word,
+                attempt,
+                error,
+                ..
+            } => bar.println(format!(
+                "retry {} after attempt {}: {}",
+                word, attempt, error
+            )),
+            ProgressEvent::Succeeded { .. } => bar.inc(1),
+            ProgressEvent::Failed { word, error } => {
+                bar.println(format!("failed {}: {}", word, error));
+                bar.inc(1);
+            }
+            ProgressEvent::BatchFinished { succeeded, failed } => {
+                bar.finish_with_message(format!("{} succeeded,{} failed", succeeded, failed))
+            }
+        })
+    }
+
+    pub(crate) fn emit(&self, event: ProgressEvent) {
+        (self.callback)(&event)
+    }
+}
+
+#[test]
+fn test_from_sender() {
+    let (tx, mut rx) = tokio::sync::mpsc::unbounded_channel();
+    let handler = ProgressHandler::from_sender(tx);
+    handler.emit(ProgressEvent::Succeeded { word: "a".into() });
+    drop(handler);
+
+    assert_eq!(
+        rx.blocking_recv(),
+        Some(ProgressEvent::Succeeded { word: "a".into() })
+    );
+    assert_eq!(rx.blocking_recv(), None);
+}
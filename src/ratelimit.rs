@@ -0,0 +1,105 @@
+//! limit requests per second sent to one host,so that large crawls stay polite
+//! and do not get throttled or blocked.
+use std::{
+    collections::HashMap,
+    sync::Mutex,
+    time::{Duration, Instant},
+};
+
+/// token bucket settings of one site.
+///
+/// bucket is refilled with `requests_per_second` tokens every second and holds at
+/// most `burst` tokens,every request takes one token.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct RateLimit {
+    requests_per_second: f64,
+    burst: u32,
+}
+
+impl Default for RateLimit {
+    fn default() -> Self {
+        Self {
+            requests_per_second: 5.0,
+            burst: 5,
+        }
+    }
+}
+
+impl RateLimit {
+    pub fn new(requests_per_second: f64, burst: u32) -> Self {
+        Self {
+            requests_per_second,
+            burst: burst.max(1),
+        }
+    }
+
+    pub fn requests_per_second(&self) -> f64 {
+        self.requests_per_second
+    }
+
+    pub fn burst(&self) -> u32 {
+        self.burst
+    }
+}
+
+#[derive(Debug)]
+struct Bucket {
+    /// may be negative,which means tokens already promised to waiting requests
+    tokens: f64,
+    last: Instant,
+}
+
+/// token buckets keyed by host,shared by all tasks using the same fetcher.
+#[derive(Debug, Default)]
+pub(crate) struct RateLimiter {
+    buckets: Mutex<HashMap<String, Bucket>>,
+}
+
+impl RateLimiter {
+    /// wait until a request to `host` is allowed by `limit`.
+    pub(crate) async fn acquire(&self, host: &str, limit: &RateLimit) {
+        let wait = self.reserve(host, limit);
+        if !wait.is_zero() {
+            tokio::time::sleep(wait).await;
+        }
+    }
+
+    /// take one token and return how long to wait before it can be used.
+    fn reserve(&self, host: &str, limit: &RateLimit) -> Duration {
+        let rate = limit.requests_per_second;
+        if rate <= 0.0 || !rate.is_finite() {
+            return Duration::ZERO;
+        }
+        let burst = f64::from(limit.burst);
+        let now = Instant::now();
+        let mut buckets = self.buckets.lock().unwrap();
+        let bucket = buckets.entry(host.into()).or_insert(Bucket {
+            tokens: burst,
+            last: now,
+        });
+        let elapsed = now.duration_since(bucket.last).as_secs_f64();
+        bucket.tokens = (bucket.tokens + elapsed * rate).min(burst);
+        bucket.last = now;
+        bucket.tokens -= 1.0;
+        if bucket.tokens >= 0.0 {
+            Duration::ZERO
+        } else {
+            Duration::from_secs_f64(-bucket.tokens / rate)
+        }
+    }
+}
+
+#[test]
+fn test_reserve() {
+    let limiter = RateLimiter::default();
+    let limit = RateLimit::new(10.0, 2);
+    let waits = (0..4)
+        .map(|_| limiter.reserve("www.zdic.net", &limit))
+        .collect::<Vec<_>>();
+    // burst is used up at once,then one request every 100ms
+    assert!(waits[0].is_zero() && waits[1].is_zero());
+    assert!(waits[2] > Duration::from_millis(90) && waits[2] <= Duration::from_millis(100));
+    assert!(waits[3] > Duration::from_millis(190) && waits[3] <= Duration::from_millis(200));
+    // other hosts have their own budget
+    assert!(limiter.reserve("www.vocabulary.com", &limit).is_zero());
+}
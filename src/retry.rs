@@ -0,0 +1,210 @@
+//! retry failed requests with exponential backoff.
+use crate::error::{Error, Result};
+use futures::Future;
+use std::time::Duration;
+
+/// decide how many times and how long to wait before a failed query is sent again.
+///
+/// only errors for which [`Error::is_retryable`](crate::Error::is_retryable) is true
+/// are retried,others fail right away.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RetryPolicy {
+    /// attempts in total including the first one
+    max_attempts: u32,
+    /// delay before the second attempt,doubled for every next attempt
+    base_delay: Duration,
+    max_delay: Duration,
+    /// randomize delay into `[delay/2,delay]` so that concurrent tasks do not retry at once
+    jitter: bool,
+}
+
+impl Default for RetryPolicy {
+    fn default() -> Self {
+        Self {
+            max_attempts: 3,
+            base_delay: Duration::from_millis(500),
+            max_delay: Duration::from_secs(30),
+            jitter: true,
+        }
+    }
+}
+
+impl RetryPolicy {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// never retry
+    pub fn none() -> Self {
+        Self {
+            max_attempts: 1,
+            ..Default::default()
+        }
+    }
+
+    pub fn set_max_attempts(&mut self, max_attempts: u32) {
+        self.max_attempts = max_attempts.max(1);
+    }
+
+    pub fn set_base_delay(&mut self, base_delay: Duration) {
+        self.base_delay = base_delay;
+    }
+
+    pub fn set_max_delay(&mut self, max_delay: Duration) {
+        self.max_delay = max_delay;
+    }
+
+    pub fn set_jitter(&mut self, jitter: bool) {
+        self.jitter = jitter;
+    }
+
+    pub fn max_attempts(&self) -> u32 {
+        self.max_attempts
+    }
+
+    pub fn base_delay(&self) -> Duration {
+        self.base_delay
+    }
+
+    pub fn max_delay(&self) -> Duration {
+        self.max_delay
+    }
+
+    pub fn jitter(&self) -> bool {
+        self.jitter
+    }
+
+    /// delay before attempt `attempt + 1`,`attempt` starts from 1.
+    pub fn delay(&self, attempt: u32) -> Duration {
+        let factor = 2u32.saturating_pow(attempt.saturating_sub(1));
+        let delay = self.base_delay.saturating_mul(factor).min(self.max_delay);
+        if self.jitter {
+            let half = delay / 2;
+            half + half.mul_f64(fastrand::f64())
+        } else {
+            delay
+        }
+    }
+
+    /// call `task` until it succeeds,fails with an error not worth retrying
+    /// or attempts run out.
+    ///
+    /// `Retry-After` sent by server takes the place of computed delay,a wait longer
+    /// than max delay fails the task right away instead of holding its batch slot.
+    ///
+    /// `on_retry` is called with attempt number,error and delay before every retry.
+    ///
+    /// return the last result and how many attempts were made.
+    pub(crate) async fn run<T, F, Fut, N>(&self, mut task: F, mut on_retry: N) -> (Result<T>, u32)
+    where
+        F: FnMut() -> Fut,
+        Fut: Future<Output = Result<T>>,
+        N: FnMut(u32, &Error, Duration),
+    {
+        let mut attempt = 0;
+        loop {
+            attempt += 1;
+            match task().await {
+                Ok(v) => return (Ok(v), attempt),
+                Err(e) if attempt < self.max_attempts && e.is_retryable() => {
+                    let delay = e.retry_after().unwrap_or_else(|| self.delay(attempt));
+                    if delay > self.max_delay {
+                        return (Err(e), attempt);
+                    }
+                    on_retry(attempt, &e, delay);
+                    tokio::time::sleep(delay).await;
+                }
+                Err(e) => return (Err(e), attempt),
+            }
+        }
+    }
+}
+
+#[test]
+fn test_delay() {
+    let mut policy = RetryPolicy::new();
+    policy.set_jitter(false);
+    policy.set_base_delay(Duration::from_secs(1));
+    policy.set_max_delay(Duration::from_secs(5));
+    let delays = (1..=5).map(|a| policy.delay(a)).collect::<Vec<_>>();
+    assert_eq!(delays, [1, 2, 4, 5, 5].map(Duration::from_secs));
+
+    policy.set_jitter(true);
+    let d = policy.delay(2);
+    assert!(d >= Duration::from_secs(1) && d <= Duration::from_secs(2));
+}
+
+#[test]
+fn test_run() {
+    use std::cell::Cell;
+    use tokio::runtime::Runtime;
+
+    let status_error =
+        |status| Error::from_status(status, Default::default(), Some(Duration::ZERO));
+    let rt = Runtime::new().unwrap();
+    let policy = RetryPolicy::new();
+
+    // 503 is retried until attempts run out
+    let attempts = Cell::new(0);
+    let (ret, n): (Result<()>, _) = rt.block_on(policy.run(
+        || {
+            attempts.set(attempts.get() + 1);
+            async { Err(status_error(503)) }
+        },
+        |_, _, _| {},
+    ));
+    assert!(ret.is_err());
+    assert_eq!(attempts.get(), 3);
+    assert_eq!(n, 3);
+
+    // 404 fails right away
+    attempts.set(0);
+    let (ret, n): (Result<()>, _) = rt.block_on(policy.run(
+        || {
+            attempts.set(attempts.get() + 1);
+            async { Err(status_error(404)) }
+        },
+        |_, _, _| {},
+    ));
+    assert!(ret.is_err());
+    assert_eq!(attempts.get(), 1);
+    assert_eq!(n, 1);
+
+    // 429 succeeds on second attempt,retry is reported once
+    attempts.set(0);
+    let mut retried = vec![];
+    let (ret, _) = rt.block_on(policy.run(
+        || {
+            attempts.set(attempts.get() + 1);
+            let n = attempts.get();
+            async move {
+                if n == 1 {
+                    Err(status_error(429))
+                } else {
+                    Ok(n)
+                }
+            }
+        },
+        |attempt, _, delay| retried.push((attempt, delay)),
+    ));
+    assert_eq!(ret.unwrap(), 2);
+    assert_eq!(retried, [(1, Duration::ZERO)]);
+
+    // Retry-After longer than max delay is not waited for
+    attempts.set(0);
+    let (ret, n): (Result<()>, _) = rt.block_on(policy.run(
+        || {
+            attempts.set(attempts.get() + 1);
+            async {
+                Err(Error::from_status(
+                    429,
+                    Default::default(),
+                    Some(Duration::from_secs(86400)),
+                ))
+            }
+        },
+        |_, _, _| panic!("should not retry"),
+    ));
+    assert!(matches!(ret, Err(Error::RateLimited { .. })));
+    assert_eq!(n, 1);
+}
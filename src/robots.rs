@@ -0,0 +1,236 @@
+//! parse robots.txt and decide whether a url may be crawled.
+//!
+//! rules follow RFC 9309: the group of the most specific matching user agent is
+//! used (or `*`),the longest matching rule wins and `Allow` wins a tie,`*` in a
+//! rule matches any characters and a trailing `$` anchors it to the end of path.
+//!
+//! `Crawl-delay` is not part of the RFC but is still honored by [`HttpFetcher`]
+//! through its rate limiter.
+//!
+//! [`HttpFetcher`]: crate::HttpFetcher
+use std::{
+    collections::HashMap,
+    sync::{Arc, Mutex},
+    time::Duration,
+};
+use tokio::sync::OnceCell;
+
+#[derive(Debug, Clone, PartialEq)]
+struct Rule {
+    allow: bool,
+    /// percent-encoded path pattern
+    pattern: String,
+}
+
+#[derive(Debug, Clone, Default, PartialEq)]
+struct Group {
+    /// lowercase product tokens
+    agents: Vec<String>,
+    rules: Vec<Rule>,
+    crawl_delay: Option<Duration>,
+}
+
+/// parsed robots.txt of one host,default one allows everything
+#[derive(Debug, Clone, Default, PartialEq)]
+pub(crate) struct Robots {
+    groups: Vec<Group>,
+}
+
+impl Robots {
+    /// robots.txt of a host which could not be reached,RFC 9309 treats it as disallowing
+    /// everything
+    pub(crate) fn disallow_all() -> Self {
+        Self {
+            groups: vec![Group {
+                agents: vec!["*".into()],
+                rules: vec![Rule {
+                    allow: false,
+                    pattern: "/".into(),
+                }],
+                crawl_delay: None,
+            }],
+        }
+    }
+
+    pub(crate) fn parse(text: &str) -> Self {
+        let mut groups: Vec<Group> = vec![];
+        // whether the last line was a user agent,consecutive ones share one group
+        let mut in_agents = false;
+        for line in text.lines() {
+            let line = line.split('#').next().unwrap_or_default();
+            let Some((key, value)) = line.split_once(':') else {
+                continue;
+            };
+            let (key, value) = (key.trim().to_ascii_lowercase(), value.trim());
+            if key == "user-agent" {
+                if !in_agents {
+                    groups.push(Group::default());
+                }
+                in_agents = true;
+                if let Some(group) = groups.last_mut() {
+                    group.agents.push(value.to_ascii_lowercase());
+                }
+                continue;
+            }
+            in_agents = false;
+            // rules before any user agent belong to no group
+            let Some(group) = groups.last_mut() else {
+                continue;
+            };
+            match key.as_str() {
+                "allow" | "disallow" if !value.is_empty() => group.rules.push(Rule {
+                    allow: key == "allow",
+                    pattern: percent_encode(value),
+                }),
+                "crawl-delay" => {
+                    group.crawl_delay = value
+                        .parse::<f64>()
+                        .ok()
+                        .filter(|d| d.is_finite() && *d >= 0.0)
+                        .map(Duration::from_secs_f64)
+                }
+                _ => {}
+            }
+        }
+        Self { groups }
+    }
+
+    /// groups applying to `user_agent`.
+    ///
+    /// a group names a product token such as `googlebot`,the longest token found in
+    /// `user_agent` wins,otherwise groups of `*` apply.
+    fn groups(&self, user_agent: &str) -> impl Iterator<Item = &Group> {
+        let user_agent = user_agent.to_ascii_lowercase();
+        let token = self
+            .groups
+            .iter()
+            .flat_map(|g| &g.agents)
+            .filter(|a| *a != "*" && user_agent.contains(a.as_str()))
+            .max_by_key(|a| a.len())
+            .map_or("*", String::as_str);
+        self.groups
+            .iter()
+            .filter(move |g| g.agents.iter().any(|a| a == token))
+    }
+
+    /// whether `path` (with query) may be fetched by `user_agent`
+    pub(crate) fn is_allowed(&self, user_agent: &str, path: &str) -> bool {
+        if path == "/robots.txt" {
+            return true;
+        }
+        self.groups(user_agent)
+            .flat_map(|g| &g.rules)
+            .filter(|r| matches(&r.pattern, path))
+            .max_by_key(|r| (r.pattern.len(), r.allow))
+            .is_none_or(|r| r.allow)
+    }
+
+    pub(crate) fn crawl_delay(&self, user_agent: &str) -> Option<Duration> {
+        self.groups(user_agent).find_map(|g| g.crawl_delay)
+    }
+}
+
+/// robots.txt of every host a fetcher has visited,each one is fetched once it succeeds.
+#[derive(Debug, Default)]
+pub(crate) struct RobotsCache {
+    hosts: Mutex<HashMap<String, Arc<OnceCell<Robots>>>>,
+}
+
+impl RobotsCache {
+    /// robots.txt of `origin`,`fetch` is called only until it returns one.
+    ///
+    /// `None` from `fetch` means robots.txt could not be fetched,everything is disallowed
+    /// for this caller and nothing is cached,so the next caller tries again.
+    pub(crate) async fn get<F, Fut>(&self, origin: &str, fetch: F) -> Robots
+    where
+        F: FnOnce() -> Fut,
+        Fut: std::future::Future<Output = Option<Robots>>,
+    {
+        let cell = self
+            .hosts
+            .lock()
+            .unwrap()
+            .entry(origin.into())
+            .or_default()
+            .clone();
+        cell.get_or_try_init(|| async { fetch().await.ok_or(()) })
+            .await
+            .cloned()
+            .unwrap_or_else(|_| Robots::disallow_all())
+    }
+}
+
+/// whether `pattern` matches beginning of `path`
+fn matches(pattern: &str, path: &str) -> bool {
+    let (pattern, anchored) = match pattern.strip_suffix('$') {
+        Some(p) => (p, true),
+        None => (pattern, false),
+    };
+    let mut parts = pattern.split('*');
+    let first = parts.next().unwrap_or_default();
+    let Some(mut rest) = path.strip_prefix(first) else {
+        return false;
+    };
+    let parts = parts.collect::<Vec<_>>();
+    for (i, part) in parts.iter().enumerate() {
+        // last part of an anchored pattern must end the path
+        if anchored && i == parts.len() - 1 {
+            return rest.ends_with(part);
+        }
+        match rest.find(part) {
+            Some(at) => rest = &rest[at + part.len()..],
+            None => return false,
+        }
+    }
+    !anchored || rest.is_empty()
+}
+
+/// encode non-ascii bytes of a rule,so it can be compared with paths of urls
+fn percent_encode(s: &str) -> String {
+    s.bytes()
+        .map(|b| {
+            if b.is_ascii() {
+                char::from(b).to_string()
+            } else {
+                format!("%{:02X}", b)
+            }
+        })
+        .collect()
+}
+
+#[test]
+fn test_robots() {
+    let robots = Robots::parse(
+        "# comment
+Disallow: /ignored
+User-agent: crawlins
+user-agent: OtherBot
+Disallow: /private
+Allow: /private/open$
+Crawl-delay: 2
+
+User-agent: *
+Disallow: /*.php
+Disallow: /hans/欲
+Crawl-delay: 0.5",
+    );
+    let ours = "Mozilla/5.0 crawlins/0.1";
+    assert!(!robots.is_allowed(ours, "/private/x"));
+    assert!(robots.is_allowed(ours, "/private/open"));
+    assert!(!robots.is_allowed(ours, "/private/open/x"));
+    assert!(robots.is_allowed(ours, "/a.php"));
+    assert!(robots.is_allowed(ours, "/robots.txt"));
+    assert_eq!(robots.crawl_delay(ours), Some(Duration::from_secs(2)));
+
+    let browser = "Mozilla/5.0 Chrome/124.0.0.0";
+    assert!(robots.is_allowed(browser, "/private/x"));
+    assert!(!robots.is_allowed(browser, "/a/b.php?x=1"));
+    assert!(!robots.is_allowed(browser, "/hans/%E6%AC%B2%E7%9B%96"));
+    assert!(robots.is_allowed(browser, "/ignored"));
+    assert_eq!(
+        robots.crawl_delay(browser),
+        Some(Duration::from_millis(500))
+    );
+
+    assert!(Robots::default().is_allowed(browser, "/private"));
+}
@@ -0,0 +1,151 @@
+//! settings every site shares: where entries are,how pages are fetched and how batch
+//! queries run.
+//!
+//! each [`Dictionary`](crate::Dictionary) wraps a [`Site`] and dereferences to it,so
+//! e.g. `HanDian::new().set_retry(..)` configures the wrapped one.
+use crate::{
+    batch::BatchOptions, config::SourceConfig, fetch::HttpFetcher, fetcher::Fetcher,
+    progress::ProgressHandler, retry::RetryPolicy,
+};
+use std::{path::PathBuf, time::Duration};
+use tokio_util::sync::CancellationToken;
+
+#[derive(Debug, Clone)]
+pub struct Site<F = HttpFetcher> {
+    config: SourceConfig,
+    fetcher: F,
+    batch: BatchOptions,
+}
+
+impl Site {
+    pub fn new(config: SourceConfig) -> Self {
+        Self::with_fetcher(config, HttpFetcher::default())
+    }
+}
+
+impl<F: Fetcher> Site<F> {
+    /// use any transport,e.g. a [`MemoryFetcher`](crate::MemoryFetcher) in tests
+    pub fn with_fetcher(config: SourceConfig, fetcher: F) -> Self {
+        Self {
+            config,
+            fetcher,
+            batch: BatchOptions::default(),
+        }
+    }
+
+    pub fn set_config(&mut self, config: SourceConfig) {
+        self.config = config;
+    }
+
+    pub fn config(&self) -> &SourceConfig {
+        &self.config
+    }
+
+    /// share one fetcher,and so its connection pool,between sites
+    pub fn set_fetcher(&mut self, fetcher: F) {
+        self.fetcher = fetcher;
+    }
+
+    pub fn fetcher(&self) -> &F {
+        &self.fetcher
+    }
+
+    pub fn set_batch_options(&mut self, batch: BatchOptions) {
+        self.batch = batch;
+    }
+
+    pub fn batch_options(&self) -> &BatchOptions {
+        &self.batch
+    }
+
+    pub fn batch_options_mut(&mut self) -> &mut BatchOptions {
+        &mut self.batch
+    }
+
+    /// retry policy applied to every word of a batch query
+    pub fn set_retry(&mut self, retry: RetryPolicy) {
+        self.batch.set_retry(retry);
+    }
+
+    pub fn retry(&self) -> &RetryPolicy {
+        self.batch.retry()
+    }
+
+    pub fn set_concurrency(&mut self, concurrency: usize) {
+        self.batch.set_concurrency(concurrency);
+    }
+
+    pub fn concurrency(&self) -> usize {
+        self.batch.concurrency()
+    }
+
+    /// make batch queries resumable,see [`BatchOptions::set_checkpoint`]
+    pub fn set_checkpoint<P: Into<PathBuf>>(&mut self, checkpoint: Option<P>) {
+        self.batch.set_checkpoint(checkpoint);
+    }
+
+    /// save failures of batch queries as json,see [`BatchOptions::set_report`]
+    pub fn set_report<P: Into<PathBuf>>(&mut self, report: Option<P>) {
+        self.batch.set_report(report);
+    }
+
+    /// subscribe to progress of batch queries
+    pub fn set_progress(&mut self, progress: Option<ProgressHandler>) {
+        self.batch.set_progress(progress);
+    }
+
+    /// time allowed to a whole batch query
+    pub fn set_deadline(&mut self, deadline: Option<Duration>) {
+        self.batch.set_deadline(deadline);
+    }
+
+    /// stop batch queries once `cancellation` is cancelled
+    pub fn set_cancellation(&mut self, cancellation: Option<CancellationToken>) {
+        self.batch.set_cancellation(cancellation);
+    }
+}
+
+/// let a site type `$site<F>` with field `site: Site<F>` dereference to it
+macro_rules! deref_site {
+    ($site:ident) => {
+        impl<F> std::ops::Deref for $site<F> {
+            type Target = $crate::site::Site<F>;
+
+            fn deref(&self) -> &Self::Target {
+                &self.site
+            }
+        }
+
+        impl<F> std::ops::DerefMut for $site<F> {
+            fn deref_mut(&mut self) -> &mut Self::Target {
+                &mut self.site
+            }
+        }
+    };
+}
+pub(crate) use deref_site;
+
+#[test]
+fn test_deref_site() {
+    struct Wrapper<F> {
+        site: Site<F>,
+    }
+    deref_site!(Wrapper);
+    let mut wrapper = Wrapper {
+        site: Site::new(SourceConfig::new("https://www.zdic.net/hans/")),
+    };
+    wrapper.set_concurrency(3);
+    wrapper
+        .batch_options_mut()
+        .set_deadline(Some(Duration::from_secs(1)));
+    assert_eq!(wrapper.concurrency(), 3);
+    assert_eq!(wrapper.site.batch_options().concurrency(), 3);
+    assert_eq!(
+        wrapper.batch_options().deadline(),
+        Some(Duration::from_secs(1))
+    );
+    assert_eq!(
+        wrapper.config().entry_url("x").unwrap(),
+        "https://www.zdic.net/hans/x"
+    );
+}
@@ -1,12 +1,14 @@
 //! include utils about how to handle requests and responses.
 use crate::error::{Error, Result};
+use futures::{stream, Future, StreamExt};
 use scraper::{Html, Selector};
-static PC:&str="Mozilla/5.0 (Windows NT 6.1) AppleWebKit/537.36 (KHTML, like Gecko) Chrome/41.0.2228.0 Safari/537.36";
+/// default user agent
+pub(crate) static PC:&str="Mozilla/5.0 (Windows NT 10.0; Win64; x64) AppleWebKit/537.36 (KHTML, like Gecko) Chrome/124.0.0.0 Safari/537.36";
 
 /// convert raw string to url code
 ///
 /// # example
-/// ```
+/// ```ignore
 /// let raw="http://a.b.c/我们";
 /// to_url_code(raw);
 ///
@@ -17,83 +19,139 @@ pub fn to_url_code<U: reqwest::IntoUrl>(raw_str: U) -> Result<String> {
     Ok(raw_str.into_url()?.to_string())
 }
 /// remove chacracters such as `\n`,`\t`
+#[cfg(feature = "vocabulary")]
 pub(crate) fn remove_escape_code(s: &str) -> String {
-    s.replace('\n', "").replace('\t', "")
-}
-/// request html page text
-pub(crate) async fn request_text(link: &str) -> Result<String> {
-    let text = reqwest::ClientBuilder::new()
-        .user_agent(PC)
-        .build()?
-        .get(link)
-        .send()
-        .await?
-        .text()
-        .await?;
-    Ok(text)
+    s.replace(['\n', '\t'], "")
 }
-
-pub(crate) async fn request_bytes(link: &str) -> Result<Vec<u8>> {
-    let text = reqwest::ClientBuilder::new()
-        .user_agent(PC)
-        .build()?
-        .get(link)
-        .send()
-        .await?
-        .bytes()
-        .await?
-        .to_vec();
-    Ok(text)
-}
-/// split a vec of values into multi-smaller vec,and put them into a vec .
+/// default number of requests kept in flight by a batch query.
+pub(crate) const DEFAULT_CONCURRENCY: usize = 15;
+/// run `task` on every item and keep at most `concurrency` of them in flight.
 ///
-/// # example
-/// ```
-/// let range=5;
-/// let v=(0..10).collect::<Vec<_>>();
-/// // [[0, 1, 2, 3, 4], [5, 6, 7, 8, 9]]
-/// println!("{:?}",group_by_range(v, range))
-///```
-pub(crate) fn group_by_range<T>(mut v: Vec<T>, range: u8) -> Vec<Vec<T>> {
-    let mut g = vec![];
-    loop {
-        if v.is_empty() {
-            break;
-        }
-        if v.len() < range.into() && !v.is_empty() {
-            g.push(v);
-            break;
-        }
-        let cs = v.drain(0..range as usize).collect::<Vec<_>>();
-        g.push(cs);
-    }
-    g
+/// unlike splitting items into fixed groups,a new task is started as soon as
+/// any running one finishes,so one slow request never holds back the others.
+///
+/// once `stop` completes no more item is started and running ones are dropped.
+///
+/// return values of finished items in completion order.
+pub(crate) async fn batch_execute<T, R, F, Fut, S>(
+    items: Vec<T>,
+    concurrency: usize,
+    stop: S,
+    task: F,
+) -> Vec<R>
+where
+    F: FnMut(T) -> Fut,
+    Fut: Future<Output = R>,
+    S: Future<Output = ()>,
+{
+    stream::iter(items)
+        .map(task)
+        .buffer_unordered(concurrency.max(1))
+        .take_until(stop)
+        .collect()
+        .await
 }
 pub(crate) fn selector_parse_frac(html: &str, selector: &str) -> Result<(Html, Selector)> {
-    let fragment = Html::parse_fragment(html);
-    match Selector::parse(selector) {
-        Ok(s) => Ok((fragment, s)),
-        Err(_) => Err(Error::ParseHtmlSelector(format!(
-            "parse {} element error",
-            selector
-        ))),
-    }
+    Ok((Html::parse_fragment(html), parse_selector(selector)?))
 }
 pub(crate) fn selector_parse_doc(html: &str, selector: &str) -> Result<(Html, Selector)> {
-    let fragment = Html::parse_document(html);
-    match Selector::parse(selector) {
-        Ok(s) => Ok((fragment, s)),
-        Err(_) => Err(Error::ParseHtmlSelector(format!(
-            "parse {} element error",
-            selector
-        ))),
-    }
+    Ok((Html::parse_document(html), parse_selector(selector)?))
+}
+/// trimmed text of all elements of `selector`,empty ones are skipped
+#[cfg(any(feature = "chengyu", feature = "hanzi"))]
+pub(crate) fn texts(document: &Html, selector: &str) -> Result<Vec<String>> {
+    Ok(document
+        .select(&parse_selector(selector)?)
+        .map(text_of)
+        .filter(|t| !t.is_empty())
+        .collect())
+}
+/// trimmed text of `element` and all its descendants
+#[cfg(any(feature = "chengyu", feature = "hanzi"))]
+pub(crate) fn text_of(element: scraper::ElementRef) -> String {
+    element.text().collect::<String>().trim().to_string()
+}
+/// parse a css selector,to select many elements from one parsed document
+pub(crate) fn parse_selector(selector: &str) -> Result<Selector> {
+    Selector::parse(selector)
+        .map_err(|_| Error::ParseHtmlSelector(format!("parse {} element error", selector)))
+}
+
+/// serve one keep-alive connection on a local port for tests.
+///
+/// every request is answered with status and body given by `respond`,or left
+/// unanswered for `None`.return address of the server and paths it was asked for.
+#[cfg(test)]
+pub(crate) async fn serve<R>(
+    mut respond: R,
+) -> (
+    std::net::SocketAddr,
+    std::sync::Arc<std::sync::Mutex<Vec<String>>>,
+)
+where
+    R: FnMut(&str) -> Option<(u16, String)> + Send + 'static,
+{
+    use tokio::io::{AsyncReadExt, AsyncWriteExt};
+    let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let addr = listener.local_addr().unwrap();
+    let requested = std::sync::Arc::new(std::sync::Mutex::new(vec![]));
+    let paths = requested.clone();
+    tokio::spawn(async move {
+        let (mut stream, _) = listener.accept().await.unwrap();
+        let mut buf = vec![0; 1024];
+        loop {
+            let n = stream.read(&mut buf).await.unwrap_or(0);
+            if n == 0 {
+                break;
+            }
+            let request = String::from_utf8_lossy(&buf[..n]).to_string();
+            let path = request.split(' ').nth(1).unwrap_or_default().to_string();
+            paths.lock().unwrap().push(path);
+            let Some((status, body)) = respond(&request) else {
+                continue;
+            };
+            let reason = reqwest::StatusCode::from_u16(status)
+                .ok()
+                .and_then(|s| s.canonical_reason())
+                .unwrap_or_default();
+            let resp = format!(
+                "HTTP/1.1 {} {}\r\ncontent-length: {}\r\n\r\n{}",
+                status,
+                reason,
+                body.len(),
+                body
+            );
+            stream.write_all(resp.as_bytes()).await.unwrap();
+        }
+    });
+    (addr, requested)
 }
 
 #[test]
-fn test_group_by_range() {
-    let range = 5;
-    let v = (0..10).collect::<Vec<_>>();
-    // [[0, 1, 2, 3, 4], [5, 6, 7, 8, 9]]
-    println!("{:?}", group_by_range(v, range))
+fn test_batch_execute() {
+    use std::sync::atomic::{AtomicUsize, Ordering};
+    use tokio::runtime::Runtime;
+
+    let running = AtomicUsize::new(0);
+    let peak = AtomicUsize::new(0);
+    let rt = Runtime::new().unwrap();
+    let mut ret = rt.block_on(batch_execute(
+        (0..20).collect(),
+        4,
+        futures::future::pending(),
+        |i: u64| {
+            let (running, peak) = (&running, &peak);
+            async move {
+                let n = running.fetch_add(1, Ordering::SeqCst) + 1;
+                peak.fetch_max(n, Ordering::SeqCst);
+                tokio::time::sleep(std::time::Duration::from_millis(20 - i)).await;
+                running.fetch_sub(1, Ordering::SeqCst);
+                i
+            }
+        },
+    ));
+    ret.sort_unstable();
+
+    assert_eq!(ret, (0..20).collect::<Vec<_>>());
+    assert_eq!(peak.load(Ordering::SeqCst), 4);
 }
@@ -1,6 +1,6 @@
 //! https://www.vocabulary.com/dictionary
 //! # Inspect the web page
-//! ```
+//! ```text
 //! word
 //! <div class="word-area">
 //!<p class="short"><i>Happy</i> is a feeling of joy,....</p>
@@ -34,13 +34,18 @@
 #![allow(dead_code)]
 
 use crate::{
-    error::Result,
-    utils::{self, group_by_range, remove_escape_code, request_text, to_url_code},
+    batch::{self, BatchOutput},
+    config::SourceConfig,
+    dictionary::Dictionary,
+    error::{Context, Error, Result},
+    fetch::HttpFetcher,
+    fetcher::Fetcher,
+    site::{deref_site, Site},
+    utils::{self, remove_escape_code},
 };
-use futures::future::join_all;
+use futures::Stream;
 use serde::{Deserialize, Serialize};
-use std::{fmt::Display, fs, io::Write, sync::Arc};
-use tokio::sync::Mutex;
+use std::{fmt::Display, fs};
 
 static WORD_AREA: &str = r#"div[class="word-area"]"#;
 static LONG: &str = r#"P[class="long"]"#;
@@ -51,7 +56,6 @@ static DEFINITION: &str = r#"div[class="definition"]"#;
 static EXAMPLE: &str = r#"div[class="example"]"#;
 static SYNONYM: &str = r#"a[class="word"]"#;
 static PREFIX_URL: &str = "https://www.vocabulary.com/dictionary/";
-static ERROR_FILE: &str = "error.txt";
 async fn query_batch_dump(words: &[String], fpath: &str) -> Result<()> {
     let ret = query_batch(words).await?;
     let js = serde_json::to_string(&ret)?;
@@ -59,90 +63,99 @@ async fn query_batch_dump(words: &[String], fpath: &str) -> Result<()> {
     Ok(())
 }
 pub async fn query_batch(words: &[String]) -> Result<Vec<Vocabulary>> {
-    let urls = words
-        .iter()
-        .map(|e| {
-            (
-                e.to_string(),
-                to_url_code(format!("{}{}", PREFIX_URL, e)).ok(),
-            )
-        })
-        .collect::<Vec<_>>();
-    let vocabs = Arc::new(Mutex::new(vec![]));
-    let error_file = Arc::new(Mutex::new(
-        fs::OpenOptions::new()
-            .create(true)
-            .append(true)
-            .open(ERROR_FILE)?,
-    ));
-    let group = group_by_range(urls, 15);
-
-    let (tx, mut rx) = tokio::sync::mpsc::channel(15);
-    tokio::spawn(async move {
-        for url_group in group {
-            if tx.send(url_group).await.is_err() {
-                println!("receiver dropped");
-                return;
-            }
+    VocabularyCom::new().query_batch(words).await
+}
+/// [`Dictionary`] of `https://www.vocabulary.com/dictionary`
+#[derive(Debug, Clone)]
+pub struct VocabularyCom<F = HttpFetcher> {
+    site: Site<F>,
+}
+
+impl Default for VocabularyCom {
+    fn default() -> Self {
+        Self {
+            site: Site::new(SourceConfig::new(PREFIX_URL)),
         }
-    });
-
-    while let Some(ug) = rx.recv().await {
-        let mut handles = vec![];
-        for (word, url) in ug {
-            let error_file = error_file.clone();
-            let vocabs = vocabs.clone();
-            let mut limit = 0;
-
-            handles.push(tokio::spawn(async move {
-                loop {
-                    limit += 1;
-                    if limit >= 3 {
-                        error_file
-                            .lock()
-                            .await
-                            .write_all(format!("{}\n", word).as_bytes())
-                            .unwrap();
-
-                        break;
-                    }
-                    if let Err(e) =
-                        run_task(vocabs.clone(), url.as_ref().unwrap(), &word.clone()).await
-                    {
-                        println!("{}", e);
-                    } else {
-                        break;
-                    }
-                }
-            }));
+    }
+}
+
+impl VocabularyCom {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn with_config(config: SourceConfig) -> Self {
+        Self {
+            site: Site::new(config),
         }
-        join_all(handles).await;
     }
+}
+
+impl<F: Fetcher> VocabularyCom<F> {
+    /// see [`Site::with_fetcher`]
+    pub fn with_fetcher(config: SourceConfig, fetcher: F) -> Self {
+        Self {
+            site: Site::with_fetcher(config, fetcher),
+        }
+    }
+}
 
-    let mut temp = vec![];
-    vocabs
-        .lock()
+deref_site!(VocabularyCom);
+
+impl<F: Fetcher> Dictionary for VocabularyCom<F> {
+    type Entry = Vocabulary;
+    const SOURCE: &'static str = "vocabulary.com";
+
+    async fn query_one(&self, word: &str) -> Result<Vocabulary> {
+        query_entry(self.fetcher(), self.config(), word).await
+    }
+
+    async fn query_batch_report(&self, words: &[String]) -> Result<BatchOutput<Vocabulary>> {
+        batch::run(
+            self.batch_options(),
+            words,
+            |w| self.config().entry_url(w),
+            |word| async move { self.query_one(&word).await },
+        )
         .await
-        .iter()
-        .for_each(|e| temp.push(e.to_owned()));
+    }
 
-    Ok(temp)
+    fn query_stream<'a>(
+        &'a self,
+        words: &[String],
+    ) -> impl Stream<Item = Result<Vocabulary>> + Send + 'a {
+        batch::stream(self.batch_options(), words, move |word| async move {
+            self.query_one(&word).await
+        })
+    }
+}
+/// fetch the page of one word and parse it into [`Vocabulary`]
+async fn query_entry(
+    fetcher: &impl Fetcher,
+    config: &SourceConfig,
+    word: &str,
+) -> Result<Vocabulary> {
+    let url = config.entry_url(word)?;
+    let html = fetcher.text(&url, config).await;
+    html.and_then(|html| parse_entry(word, &html))
+        .map_err(|e| e.with_context(<VocabularyCom as Dictionary>::SOURCE, word, &url))
 }
-async fn run_task(vocabs: Arc<Mutex<Vec<Vocabulary>>>, url: &str, word: &str) -> Result<()> {
+/// parse page of `word`,a page without any definition is [`Error::NotFound`]
+fn parse_entry(word: &str, html: &str) -> Result<Vocabulary> {
     let mut vocab = Vocabulary::new(word.into());
-    let html = request_text(url).await?;
-    let area = parse_word_area(&html)?;
-    let defs = parse_definitions(&html)?;
+    let area = parse_word_area(html)?;
+    let defs = parse_definitions(html)?;
+    if defs.is_empty() {
+        return Err(Error::NotFound(Context::default()));
+    }
 
     vocab.set_word_area(area);
     vocab.set_definitions(defs);
 
-    vocabs.lock().await.push(vocab);
-
-    Ok(())
+    Ok(vocab)
 }
 /// # sample html
-/// ```
+/// ```text
 /// <div class="word-area">
 ///<p class="short"><i>Happy</i> is a feeling of joy,....</p>
 ///<p class="long"><i>Happy</i> hails from the Middle English word <i>hap</i>, meaning... </p>
@@ -169,7 +182,7 @@ fn parse_word_area(html: &str) -> Result<Area> {
     Ok(area)
 }
 ///  html code sample
-/// ```
+/// ```text
 /// <dl class="instances">
 /// <span class="detail">synonyms:</span>
 /// <span><a href="/dictionary/felicitous" class="word">felicitous</a></span>
@@ -195,7 +208,7 @@ fn parse_synonym(html: &str) -> Result<Option<Vec<String>>> {
     Ok(if ret.is_empty() { None } else { Some(ret) })
 }
 /// # html code sample
-/// ```
+/// ```text
 ///  <div class="word-definitions">
 ///
 /// <li class="sense pos_a ... sord1" id="s101621">
@@ -247,8 +260,7 @@ fn parse_definitions(html: &str) -> Result<Vec<Definition>> {
             let elements_exam = html_exam.select(&sel_exam).collect::<Vec<_>>();
 
             let def_text = element_def
-                .as_ref()
-                .unwrap()
+                .ok_or_else(|| Error::missing_field("definition"))?
                 .text()
                 .map(remove_escape_code)
                 .collect::<Vec<_>>()
@@ -262,8 +274,9 @@ fn parse_definitions(html: &str) -> Result<Vec<Definition>> {
                         .map(|e| {
                             e.text()
                                 .map(remove_escape_code)
+                                // text nodes keep their own spaces,e.g. "a " and " outcome"
                                 .collect::<Vec<_>>()
-                                .join(" ")
+                                .join("")
                                 .trim()
                                 .to_string()
                         })
@@ -304,6 +317,18 @@ impl Vocabulary {
     pub fn set_definitions(&mut self, definitions: Vec<Definition>) {
         self.definitions = definitions;
     }
+
+    pub fn word(&self) -> &str {
+        self.word.as_ref()
+    }
+
+    pub fn word_area(&self) -> &Area {
+        &self.word_area
+    }
+
+    pub fn definitions(&self) -> &[Definition] {
+        self.definitions.as_ref()
+    }
 }
 #[derive(Debug, Default, Serialize, Deserialize, Clone, PartialEq, Eq)]
 
@@ -365,9 +390,9 @@ impl Display for Definition {
         let exam_syno = if example.is_empty() && synonym.is_empty() {
             String::new()
         } else if example.is_empty() && !synonym.is_empty() {
-            format!("{}", synonym)
+            synonym.to_string()
         } else if !example.is_empty() && synonym.is_empty() {
-            format!("{}", example)
+            example.to_string()
         } else {
             format!("{}\n{}", example, synonym)
         };
@@ -407,7 +432,7 @@ fn test_parse_word_area() {
     assert_eq!(area, a)
 }
 pub fn task(file: &str) -> Result<()> {
-    use genanki_rs::{Deck, Error, Field, Model, Note, Template};
+    use genanki_rs::{Deck, Field, Model, Note, Template};
     let v: Vec<Vocabulary> = serde_json::from_reader(fs::File::open(file)?)?;
     let my_model = Model::new(
         1607392319,
@@ -534,18 +559,24 @@ fn test_parse_synonym() {
 fn test_batch() {
     use tokio::runtime::Runtime;
     let words = ["absolutely".to_string(), "thrash".to_string()];
+    let mut vocabulary = VocabularyCom::new();
+    vocabulary.set_fetcher(crate::fixture::test_fetcher());
     let rt = Runtime::new().unwrap();
-    let ret = rt.block_on(query_batch(&words)).unwrap();
+    let ret = rt.block_on(vocabulary.query_batch(&words)).unwrap();
     println!("{:?}", ret);
+    let words = ret.iter().map(Vocabulary::word).collect::<Vec<_>>();
+    assert_eq!(words, ["absolutely", "thrash"]);
+    assert_eq!(ret[0].definitions().len(), 2);
 }
 #[test]
 fn test_t() {
     let url = "https://www.merriam-webster.com/dictionary/give";
     use tokio::runtime::Runtime;
     let rt = Runtime::new().unwrap();
-    let t = rt.block_on(request_text(url)).unwrap();
-    fs::write("give.html", t).unwrap();
-    use utils;
+    let t = rt
+        .block_on(crate::fixture::test_fetcher().text(url, &SourceConfig::new(PREFIX_URL)))
+        .unwrap();
+    assert!(t.contains("related-phrases-list-container-xs"));
     // let s= fs::read_to_string(".html").unwrap();
     // let (html, sel) = utils::selector_parse_doc(&s, r#"div[class="vg"]"#).unwrap();
     // let elements = html.select(&sel).next();
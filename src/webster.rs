@@ -1,15 +1,19 @@
 //! website: https://www.merriam-webster.com/dictionary/happy
 //!
 #![allow(dead_code)]
-use crate::error::Result;
-use crate::utils::{
-    group_by_range, request_text, selector_parse_doc, selector_parse_frac, to_url_code,
-};
-use futures::future::join_all;
+use crate::batch::{self, BatchOutput};
+use crate::config::SourceConfig;
+use crate::dictionary::Dictionary;
+use crate::error::{Context, Error, Result};
+use crate::fetch::HttpFetcher;
+use crate::fetcher::Fetcher;
+use crate::progress::ProgressHandler;
+use crate::site::{deref_site, Site};
+use crate::utils::{selector_parse_doc, selector_parse_frac};
+use futures::Stream;
+use indicatif::ProgressBar;
 use serde::{Deserialize, Serialize};
-use std::{fmt::Display, fs, io::Write, sync::Arc};
-use tokio::io::AsyncWriteExt;
-use tokio::sync::Mutex;
+use std::fs;
 static ROOT_URL: &str = "https://www.merriam-webster.com";
 static DICT: &str = "https://www.merriam-webster.com/dictionary/";
 /// there may exist more than one block
@@ -25,6 +29,8 @@ static PHRASE_WORD: &str = r#"span[id="phrases"]"#;
 
 static PHRASE_ITEM: &str = r#"span[class="drp"]"#;
 static PHRASE_MEANING: &str = r#"div[class="dt "]"#;
+/// headword of the entry,a page without it is not an entry of the word
+static HEADWORD: &str = r#"h1[class="hword"]"#;
 // phrases
 static PHRASE_BLOCK: &str = r#"div[class="related-phrases-list-container-xs"]"#;
 /// directly call text() to get phrase text,href to get partial url
@@ -51,6 +57,18 @@ impl Webster {
     pub fn set_phrases(&mut self, phrases: Option<Vec<Phrase>>) {
         self.phrases = phrases;
     }
+
+    pub fn word(&self) -> &str {
+        self.word.as_ref()
+    }
+
+    pub fn definitions(&self) -> &[Definition] {
+        self.definitions.as_ref()
+    }
+
+    pub fn phrases(&self) -> Option<&Vec<Phrase>> {
+        self.phrases.as_ref()
+    }
 }
 #[derive(Debug, Default, Serialize, Deserialize, Clone, PartialEq, Eq)]
 pub struct Definition {
@@ -80,106 +98,128 @@ impl Phrase {
     fn new() -> Self {
         Self::default()
     }
+
+    fn set_entry(&mut self, entry: String) {
+        self.entry = entry;
+    }
+
+    pub fn entry(&self) -> &str {
+        self.entry.as_ref()
+    }
+
+    pub fn definitions(&self) -> &[String] {
+        self.definitions.as_ref()
+    }
 }
 
-#[derive(Debug, Default, Serialize, Deserialize, Clone, PartialEq, Eq)]
-struct T {
-    item: String,
-    phrases: String,
+/// [`Dictionary`] of `https://www.merriam-webster.com/dictionary`
+#[derive(Debug, Clone)]
+pub struct MerriamWebster<F = HttpFetcher> {
+    site: Site<F>,
 }
 
-impl T {
-    fn new(item: String) -> Self {
+impl Default for MerriamWebster {
+    fn default() -> Self {
         Self {
-            item,
-            ..Default::default()
+            site: Site::new(SourceConfig::new(DICT)),
         }
     }
+}
 
-    fn set_phrases(&mut self, phrases: String) {
-        self.phrases = phrases;
+impl MerriamWebster {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn with_config(config: SourceConfig) -> Self {
+        Self {
+            site: Site::new(config),
+        }
     }
 }
-async fn run_task(vocabs: Arc<Mutex<Vec<T>>>, url: &str, word: &str) -> Result<()> {
-    let mut vocab = T::new(word.into());
-    let html = request_text(url).await?;
-    let phr = parse_phrase(&html)?;
-    vocab.set_phrases(phr.map_or("".to_string(), |v| v.join("\n")));
-    vocabs.lock().await.push(vocab);
-    // let phrases=parse_phrase(&html)?;
 
-    Ok(())
+impl<F: Fetcher> MerriamWebster<F> {
+    /// see [`Site::with_fetcher`]
+    pub fn with_fetcher(config: SourceConfig, fetcher: F) -> Self {
+        Self {
+            site: Site::with_fetcher(config, fetcher),
+        }
+    }
+}
+
+deref_site!(MerriamWebster);
+
+impl<F: Fetcher> Dictionary for MerriamWebster<F> {
+    type Entry = Webster;
+    const SOURCE: &'static str = "merriam-webster.com";
+
+    async fn query_one(&self, word: &str) -> Result<Webster> {
+        query_entry(self.fetcher(), self.config(), word).await
+    }
+
+    async fn query_batch_report(&self, words: &[String]) -> Result<BatchOutput<Webster>> {
+        batch::run(
+            self.batch_options(),
+            words,
+            |w| self.config().entry_url(w),
+            |word| async move { self.query_one(&word).await },
+        )
+        .await
+    }
+
+    fn query_stream<'a>(
+        &'a self,
+        words: &[String],
+    ) -> impl Stream<Item = Result<Webster>> + Send + 'a {
+        batch::stream(self.batch_options(), words, move |word| async move {
+            self.query_one(&word).await
+        })
+    }
+}
+/// fetch the page of one word and parse it into [`Webster`]
+async fn query_entry(fetcher: &impl Fetcher, config: &SourceConfig, word: &str) -> Result<Webster> {
+    let url = config.entry_url(word)?;
+    let html = fetcher.text(&url, config).await;
+    html.and_then(|html| parse_entry(word, &html))
+        .map_err(|e| e.with_context(<MerriamWebster as Dictionary>::SOURCE, word, &url))
+}
+/// parse page of `word` into [`Webster`]
+///
+/// a page without headword,e.g. one suggesting spellings,is [`Error::NotFound`],
+/// while an entry without related phrases keeps `phrases` as `None`.
+fn parse_entry(word: &str, html: &str) -> Result<Webster> {
+    let mut vocab = Webster::new(word.into());
+    let (doc, sel) = selector_parse_doc(html, HEADWORD)?;
+    if doc.select(&sel).next().is_none() {
+        return Err(Error::NotFound(Context::default()));
+    }
+    let phrases = parse_phrase(html)?.map(|v| {
+        v.into_iter()
+            .map(|p| {
+                let mut phrase = Phrase::new();
+                phrase.set_entry(p);
+                phrase
+            })
+            .collect::<Vec<_>>()
+    });
+    vocab.set_phrases(phrases);
+
+    Ok(vocab)
 }
 pub async fn gen_anki() -> Result<()> {
-    let error_file = Arc::new(Mutex::new(tokio::fs::File::open("error.txt").await?));
-    let vocabs = Arc::new(Mutex::new(Vec::new()));
     let words = fs::read_to_string("voc.txt")?
         .lines()
         .map(<str as ToString>::to_string)
         .collect::<Vec<_>>();
-    let urls = words
-        .iter()
-        .map(|e| {
-            (
-                e.to_string(),
-                to_url_code(format!(
-                    "{}{}",
-                    "https://www.merriam-webster.com/dictionary/", e
-                ))
-                .ok(),
-            )
-        })
-        .collect::<Vec<_>>();
-    let group = group_by_range(urls, 15);
-
-    let (tx, mut rx) = tokio::sync::mpsc::channel(15);
-    tokio::spawn(async move {
-        for url_group in group {
-            if tx.send(url_group).await.is_err() {
-                println!("receiver dropped");
-                return;
-            }
-        }
-    });
-
-    while let Some(ug) = rx.recv().await {
-        let mut handles = vec![];
-        for (word, url) in ug {
-            let error_file = error_file.clone();
-            let vocabs = vocabs.clone();
-            let mut limit = 0;
-
-            handles.push(tokio::spawn(async move {
-                loop {
-                    limit += 1;
-                    if limit >= 3 {
-                        error_file
-                            .lock()
-                            .await
-                            .write_all(format!("{}\n", word).as_bytes())
-                            .await
-                            .unwrap();
-
-                        break;
-                    }
-                    if let Err(e) =
-                        run_task(vocabs.clone(), url.as_ref().unwrap(), &word.clone()).await
-                    {
-                        println!("{}", e);
-                    } else {
-                        break;
-                    }
-                }
-            }));
-        }
-        join_all(handles).await;
-    }
+    let mut webster = MerriamWebster::new();
+    webster.set_progress(Some(ProgressHandler::progress_bar(ProgressBar::new(0))));
+    let vocabs = webster.query_batch(&words).await?;
 
-    make_cards(vocabs.lock().await.to_vec());
+    make_cards(vocabs);
     Ok(())
 }
 
-fn make_cards(cards: Vec<T>) {
+fn make_cards(cards: Vec<Webster>) {
     use genanki_rs::{Deck, Field, Model, Note, Template};
     let my_model = Model::new(
         1607392319,
@@ -199,11 +239,13 @@ fn make_cards(cards: Vec<T>) {
         "Deck for studying country capitals",
     );
     for c in cards {
-        let p = c.phrases.as_ref();
-        let word_href = format!("<a href=\"{}{}\">{}{}</a>", DICT, c.item, DICT, c.item);
+        let p = c.phrases.as_ref().map_or("".to_string(), |v| {
+            v.iter().map(|p| p.entry()).collect::<Vec<_>>().join("\n")
+        });
+        let word_href = format!("<a href=\"{}{}\">{}{}</a>", DICT, c.word, DICT, c.word);
         let my_note = Note::new(
             my_model.clone(),
-            vec![c.item.as_ref(), word_href.as_str(), p],
+            vec![c.word.as_ref(), word_href.as_str(), p.as_str()],
         )
         .unwrap();
         my_deck.add_note(my_note);
@@ -238,7 +280,82 @@ fn parse_phrase(html: &str) -> Result<Option<Vec<String>>> {
 
 #[test]
 fn test_parse_definition() {
-    let s = fs::read_to_string("give.html").unwrap();
+    use tokio::runtime::Runtime;
+    let url = "https://www.merriam-webster.com/dictionary/give";
+    let rt = Runtime::new().unwrap();
+    let s = rt
+        .block_on(crate::fixture::test_fetcher().text(url, &SourceConfig::new(DICT)))
+        .unwrap();
+
+    let phrases = parse_phrase(&s).unwrap().unwrap();
+    assert!(phrases.contains(&"give up".to_string()));
+}
+
+#[test]
+fn test_query_one_from_local_server() {
+    use tokio::runtime::Runtime;
+    let html = r#"<h1 class="hword">give</h1>
+<div class="related-phrases-list-container-xs">
+<a href="/dictionary/give%20up">give up</a>
+<a href="/dictionary/give%20in">give in</a>
+</div>"#;
+    let rt = Runtime::new().unwrap();
+    let (webster, requested) = rt.block_on(async {
+        let (addr, requested) = crate::utils::serve(|_| Some((200, html.into()))).await;
+        let config = SourceConfig::new(format!("http://{}/dictionary/", addr));
+        let mut webster = MerriamWebster::with_config(config);
+        let mut fetcher = HttpFetcher::default();
+        fetcher.set_respect_robots(false);
+        webster.set_fetcher(fetcher);
+        (webster.query_one("give").await.unwrap(), requested)
+    });
+    let phrases = webster
+        .phrases()
+        .unwrap()
+        .iter()
+        .map(|p| p.entry())
+        .collect::<Vec<_>>();
+
+    assert_eq!(phrases, ["give up", "give in"]);
+    assert_eq!(*requested.lock().unwrap(), ["/dictionary/give"]);
+}
+
+#[test]
+fn test_query_one_with_memory_fetcher() {
+    use crate::fetcher::{MemoryFetcher, Response};
+    use tokio::runtime::Runtime;
+    let html = r#"<h1 class="hword">take</h1>
+<div class="related-phrases-list-container-xs">
+<a href="/dictionary/take%20off">take off</a>
+</div>"#;
+    let mut fetcher = MemoryFetcher::new();
+    fetcher.insert(format!("{}take", DICT), Response::ok(html));
+    let webster = MerriamWebster::with_fetcher(SourceConfig::new(DICT), fetcher);
+    let rt = Runtime::new().unwrap();
+
+    let take = rt.block_on(webster.query_one("take")).unwrap();
+    assert_eq!(take.phrases().unwrap()[0].entry(), "take off");
+    assert!(matches!(
+        rt.block_on(webster.query_one("give")),
+        Err(crate::Error::NotFound(c)) if c.source() == "merriam-webster.com" && c.word() == "give"
+    ));
+}
+
+#[test]
+fn test_parse_entry() {
+    let html = std::fs::read_to_string(concat!(
+        env!("CARGO_MANIFEST_DIR"),
+        "/fixtures/www.merriam-webster.com/dictionary%2Fgive.html"
+    ))
+    .unwrap();
+    let give = parse_entry("give", &html).unwrap();
+    assert_eq!(give.phrases().unwrap().len(), 3);
 
-    println!("{:?}", parse_phrase(&s).unwrap());
+    // soft 404 answered 200 with spelling suggestions
+    let html =
+        r#"<h1 class="mispelled-word">The word you've entered isn't in the dictionary.</h1>"#;
+    assert!(matches!(parse_entry("giv", html), Err(Error::NotFound(_))));
+    // an entry without related phrases still gets a card
+    let giving = parse_entry("giving", r#"<h1 class="hword">giving</h1>"#).unwrap();
+    assert!(giving.phrases().is_none());
 }